@@ -39,7 +39,7 @@ async fn main() -> Result<()> {
     tokio::spawn(async move {
         while let Some(event) = event_rx.recv().await {
             match event {
-                TaskEvent::Started { task_id } => {
+                TaskEvent::Started { task_id, .. } => {
                     println!("  ⚙  {} started", task_id);
                 }
                 TaskEvent::Output { task_id, line } => {
@@ -51,10 +51,17 @@ async fn main() -> Result<()> {
                     println!("  ✓  {} completed (exit code: {})", task_id, exit_code);
                     completed_clone.lock().unwrap().push(task_id);
                 }
-                TaskEvent::Failed { task_id, error } => {
+                TaskEvent::Failed { task_id, error, .. } => {
                     println!("  ✗  {} failed: {}", task_id, error);
                     completed_clone.lock().unwrap().push(task_id);
                 }
+                TaskEvent::TimedOut { task_id, timeout } => {
+                    println!("  ⏱  {} timed out after {:?}", task_id, timeout);
+                    completed_clone.lock().unwrap().push(task_id);
+                }
+                TaskEvent::Ready { task_id } => {
+                    println!("  ●  {} ready", task_id);
+                }
             }
         }
     });
@@ -83,7 +90,7 @@ async fn main() -> Result<()> {
                 
                 if let Some(command) = &task.command {
                     // Actually start the task
-                    executor.start_task(task_id, command).await?;
+                    executor.start_task(task_id, command, task.spawn_options()?).await?;
                     scheduler.mark_started(task_id)?;
                 } else {
                     // No command, just mark as done
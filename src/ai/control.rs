@@ -78,6 +78,22 @@ pub enum ControlCommand {
     StopTask { task_id: String },
     /// Send input to task stdin
     SendInput { task_id: String, input: String },
+    /// Approve a gate (`approver` defaults to the control mode)
+    ApproveGate {
+        task_id: String,
+        #[serde(default)]
+        approver: Option<String>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Reject a gate
+    RejectGate {
+        task_id: String,
+        #[serde(default)]
+        approver: Option<String>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
     /// Request state snapshot
     GetState,
     /// Request task output
@@ -135,6 +151,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gate_commands() {
+        let cmd: ControlCommand =
+            serde_json::from_str(r#"{"action":"reject_gate","task_id":"deploy","reason":"freeze"}"#).unwrap();
+        match cmd {
+            ControlCommand::RejectGate { task_id, approver, reason } => {
+                assert_eq!(task_id, "deploy");
+                assert_eq!(approver, None);
+                assert_eq!(reason.as_deref(), Some("freeze"));
+            }
+            _ => panic!("wrong variant"),
+        }
+    }
+
     #[test]
     fn test_control_response() {
         let resp = ControlResponse::ok();
@@ -37,6 +37,17 @@ pub enum GidEvent {
         progress: f64,
         metrics: HashMap<String, serde_json::Value>,
     },
+    /// Gate is waiting for someone to approve or reject it
+    GateAwaitingApproval {
+        task_id: String,
+    },
+    /// Gate was approved or rejected
+    GateDecided {
+        task_id: String,
+        approved: bool,
+        approver: String,
+        reason: Option<String>,
+    },
     /// Advisory triggered
     AdvisoryTriggered {
         task_id: String,
@@ -55,24 +66,10 @@ pub enum GidEvent {
 impl GidEvent {
     /// Create a metrics updated event from TaskMetrics
     pub fn from_metrics(task_id: &str, metrics: &TaskMetrics) -> Self {
-        let json_metrics: HashMap<String, serde_json::Value> = metrics
-            .metrics
-            .iter()
-            .map(|(k, v)| {
-                let jv = match v {
-                    crate::semantic::MetricValue::Float(f) => serde_json::json!(f),
-                    crate::semantic::MetricValue::Int(i) => serde_json::json!(i),
-                    crate::semantic::MetricValue::String(s) => serde_json::json!(s),
-                    crate::semantic::MetricValue::Bool(b) => serde_json::json!(b),
-                };
-                (k.clone(), jv)
-            })
-            .collect();
-
         GidEvent::MetricsUpdated {
             task_id: task_id.to_string(),
             progress: metrics.progress as f64,
-            metrics: json_metrics,
+            metrics: metrics_to_json(metrics),
         }
     }
 
@@ -95,6 +92,23 @@ impl GidEvent {
     }
 }
 
+/// Metric values of a task as JSON
+pub fn metrics_to_json(metrics: &TaskMetrics) -> HashMap<String, serde_json::Value> {
+    metrics
+        .metrics
+        .iter()
+        .map(|(k, v)| {
+            let jv = match v {
+                crate::semantic::MetricValue::Float(f) => serde_json::json!(f),
+                crate::semantic::MetricValue::Int(i) => serde_json::json!(i),
+                crate::semantic::MetricValue::String(s) => serde_json::json!(s),
+                crate::semantic::MetricValue::Bool(b) => serde_json::json!(b),
+            };
+            (k.clone(), jv)
+        })
+        .collect()
+}
+
 /// Broadcast-based event stream for multiple consumers
 pub struct EventStream {
     tx: broadcast::Sender<GidEvent>,
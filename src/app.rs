@@ -1,7 +1,14 @@
 //! Application state and main event loop
 
 use crate::agents::{AgentManager, AgentRuntimeStatus, AgentType};
-use crate::core::{Executor, Graph, Scheduler, TaskEvent};
+use crate::ai::control::{ControlCommand, ControlResponse, StateSnapshot, TaskSnapshot};
+use crate::ai::events::metrics_to_json;
+use crate::ai::{ControlMode, EventStream, GidEvent};
+use crate::core::{
+    resolve_task, Executor, FileWatcher, Graph, GraphTaskStatus, HumanDuration, OutputCapture,
+    RestartHistory, RestartMode, RestartPolicy, RetryPolicy, Scheduler, Task, TaskCache, TaskEvent,
+    TaskSelection, VarContext, OUTPUT_FILE_ENV,
+};
 use crate::notifications::NotificationManager;
 use crate::ports::PortManager;
 use crate::semantic::advisor::{Advisory, SmartAdvisor};
@@ -9,10 +16,13 @@ use crate::semantic::commands::TaskCommands;
 use crate::semantic::history::{self, TaskMetricHistory};
 use crate::semantic::parsers::{BuildParser, MLTrainingParser, RegexParser};
 use crate::semantic::{MetricValue, ParserRegistry, TaskMetrics};
-use crate::session::{Session, TaskStatus};
+use crate::session::{Approval, Session, TaskStatus};
 use anyhow::Result;
+use chrono::Utc;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
-use std::collections::HashMap;
+use crate::workspace::Workspace;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
@@ -74,6 +84,70 @@ impl AgentStatus {
     }
 }
 
+/// Where the running graph was loaded from, so it can be reloaded
+#[derive(Debug, Clone)]
+pub enum GraphSource {
+    /// A single graph file
+    File(PathBuf),
+    /// The projects of a workspace (reloaded from their graph files)
+    Workspace(Workspace),
+}
+
+impl GraphSource {
+    /// Load the graph again, validated
+    fn load(&self) -> Result<Graph> {
+        match self {
+            Self::File(path) => Graph::from_file(path),
+            Self::Workspace(workspace) => {
+                let mut workspace = workspace.clone();
+                for project in workspace.projects.values_mut() {
+                    project.graph = Graph::from_file(&project.graph_path)
+                        .map_err(|e| anyhow::anyhow!("{}: {}", project.name, e))?;
+                }
+                workspace.ensure_valid()?;
+                Ok(workspace.to_unified_graph())
+            }
+        }
+    }
+
+    /// Graph files to watch: the source files and the files they include
+    fn files(&self, graph: &Graph) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = match self {
+            Self::File(path) => vec![path.clone()],
+            Self::Workspace(workspace) => workspace
+                .projects
+                .values()
+                .map(|p| p.graph_path.clone())
+                .collect(),
+        };
+        files.extend(graph.all_tasks().values().filter_map(|t| t.included_from.clone()));
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+/// Result of the last graph reload, shown in the TUI header
+#[derive(Debug, Clone)]
+pub struct ReloadBanner {
+    pub message: String,
+    /// The graph failed to load or validate; the previous graph is still used
+    pub is_error: bool,
+    pub at: Instant,
+}
+
+/// How long a successful reload stays in the header
+const RELOAD_BANNER_DURATION: Duration = Duration::from_secs(10);
+
+/// Approve/reject decision being typed in the TUI
+#[derive(Debug, Clone)]
+pub struct ApprovalPrompt {
+    pub task_id: String,
+    pub approve: bool,
+    /// Optional reason typed so far
+    pub reason: String,
+}
+
 /// Project summary for unified dashboard
 #[derive(Debug, Clone)]
 pub struct ProjectSummary {
@@ -85,6 +159,8 @@ pub struct ProjectSummary {
     pub tasks_running: usize,
     pub tasks_failed: usize,
     pub recent_event: Option<String>,
+    /// Other projects this project's tasks depend on, sorted
+    pub depends_on_projects: Vec<String>,
 }
 
 /// Application state
@@ -112,11 +188,43 @@ pub struct App {
     pub selected_project: usize,
     pub search_query: String,
     pub search_mode: bool,
+    /// Gate decision being entered (`a` / `x` on a gate awaiting approval)
+    pub approval_prompt: Option<ApprovalPrompt>,
     pub recent_events: Vec<(Instant, String, String)>, // (time, project, message)
     pub task_start_times: HashMap<String, Instant>,
+    /// Task currently holding each project's port (project -> task ID)
+    pub port_holders: HashMap<String, String>,
+    /// Failed tasks waiting to be retried (task ID -> (due time, next attempt))
+    pub pending_retries: HashMap<String, (Instant, u32)>,
+    /// Exited tasks waiting to be restarted (task ID -> due time)
+    pub pending_restarts: HashMap<String, Instant>,
+    /// Restart counts and last exit reasons of tasks with a restart policy
+    pub restarts: HashMap<String, RestartHistory>,
+    /// Watched files of tasks with `watch:` (set by `run --watch`)
+    pub watcher: Option<FileWatcher>,
+    /// Runs stopped to re-run after a file change; their exit is not reported
+    superseded: HashSet<String>,
+    /// Graph file(s) to reload when they change
+    graph_source: Option<GraphSource>,
+    graph_watcher: Option<FileWatcher>,
+    reload_banner: Option<ReloadBanner>,
+    /// Tasks picked on the command line, applied again on reload
+    selection: Option<TaskSelection>,
+    /// Tasks stopped by the user, which must not be retried
+    stopped_tasks: HashSet<String>,
+    /// Input/output cache for tasks with `inputs:`
+    pub cache: TaskCache,
+    /// Input fingerprints of running tasks, recorded when they succeed
+    fingerprints: HashMap<String, String>,
+    /// Output values being captured from running tasks
+    captures: HashMap<String, OutputCapture>,
+    /// Semantic command templates with their variables resolved at launch
+    semantic_commands: HashMap<String, HashMap<String, String>>,
     // Phase 2: Agent Integration
     pub agent_manager: AgentManager,
     pub last_agent_scan: Instant,
+    /// Events for control-mode consumers (gate approvals)
+    pub events: EventStream,
 }
 
 impl App {
@@ -168,11 +276,28 @@ impl App {
             selected_project: 0,
             search_query: String::new(),
             search_mode: false,
+            approval_prompt: None,
             recent_events: Vec::new(),
             task_start_times: HashMap::new(),
+            port_holders: HashMap::new(),
+            pending_retries: HashMap::new(),
+            pending_restarts: HashMap::new(),
+            restarts: HashMap::new(),
+            watcher: None,
+            superseded: HashSet::new(),
+            graph_source: None,
+            graph_watcher: None,
+            reload_banner: None,
+            selection: None,
+            stopped_tasks: HashSet::new(),
+            cache: TaskCache::default(),
+            fingerprints: HashMap::new(),
+            captures: HashMap::new(),
+            semantic_commands: HashMap::new(),
             // Phase 2: Agent Integration
             agent_manager,
             last_agent_scan: Instant::now(),
+            events: EventStream::default(),
         }
     }
 
@@ -189,7 +314,11 @@ impl App {
         // Initialize port manager and allocate ports for all projects
         let mut port_manager = PortManager::default();
         for (idx, name) in project_names.iter().enumerate() {
-            let preferred_port = 3000 + idx as u16;
+            let preferred_port = workspace
+                .projects
+                .get(name)
+                .and_then(|p| p.port)
+                .unwrap_or(3000 + idx as u16);
             if let Err(e) = port_manager.allocate(name, Some(preferred_port)) {
                 log::warn!("Failed to allocate port for {}: {}", name, e);
             }
@@ -225,11 +354,28 @@ impl App {
             selected_project: 0,
             search_query: String::new(),
             search_mode: false,
+            approval_prompt: None,
             recent_events: Vec::new(),
             task_start_times: HashMap::new(),
+            port_holders: HashMap::new(),
+            pending_retries: HashMap::new(),
+            pending_restarts: HashMap::new(),
+            restarts: HashMap::new(),
+            watcher: None,
+            superseded: HashSet::new(),
+            graph_source: None,
+            graph_watcher: None,
+            reload_banner: None,
+            selection: None,
+            stopped_tasks: HashSet::new(),
+            cache: TaskCache::default(),
+            fingerprints: HashMap::new(),
+            captures: HashMap::new(),
+            semantic_commands: HashMap::new(),
             // Phase 2: Agent Integration
             agent_manager,
             last_agent_scan: Instant::now(),
+            events: EventStream::default(),
         }
     }
 
@@ -251,23 +397,88 @@ impl App {
 
     /// Start all ready tasks
     pub async fn start_ready_tasks(&mut self) -> Result<()> {
-        let ready = self.scheduler.schedule_next();
+        // Retries whose backoff has elapsed
+        let now = Instant::now();
+        let mut due: Vec<String> = self
+            .pending_retries
+            .iter()
+            .filter(|(_, (at, _))| *at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        due.sort();
+        for task_id in due {
+            if let Some((_, attempt)) = self.pending_retries.remove(&task_id) {
+                log::info!("Retrying task: {} (attempt {})", task_id, attempt);
+                self.session.start_attempt(task_id.clone(), attempt);
+                if let Err(e) = self.launch_task(&task_id).await {
+                    self.handle_task_failed(&task_id, &format!("Failed to start: {}", e), TaskStatus::Failed, None);
+                }
+            }
+        }
+
+        // Restarts whose backoff has elapsed
+        let mut due: Vec<String> = self
+            .pending_restarts
+            .iter()
+            .filter(|(_, at)| **at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        due.sort();
+        for task_id in due {
+            self.pending_restarts.remove(&task_id);
+            log::info!("Restarting task: {}", task_id);
+            self.session.start_task(task_id.clone());
+            let started = self.launch_task(&task_id).await;
+            // A restarted service is not ready until its probe passes again
+            self.scheduler.mark_started(&task_id)?;
+            if let Err(e) = started {
+                self.handle_task_failed(&task_id, &format!("Failed to start: {}", e), TaskStatus::Failed, None);
+            }
+        }
+
+        let mut ready = self.scheduler.schedule_next();
+        // Re-queued tasks wait for their previous run to exit
+        ready.retain(|id| !self.superseded.contains(id));
 
         for task_id in ready {
-            let task = self.scheduler.graph().get_task(&task_id).unwrap();
+            let task = self.scheduler.graph().get_task(&task_id).unwrap().clone();
 
             if let Some(command) = &task.command {
+                if self.check_cache(&task_id) {
+                    continue;
+                }
                 log::info!("Starting task: {} ({})", task_id, command);
 
                 self.session.start_task(task_id.clone());
-                self.executor.start_task(&task_id, command).await?;
+                let started = self.launch_task(&task_id).await;
                 self.scheduler.mark_started(&task_id)?;
+                if let Err(e) = started {
+                    self.handle_task_failed(&task_id, &format!("Failed to start: {}", e), TaskStatus::Failed, None);
+                }
             } else {
                 // No command, mark as done immediately
                 self.scheduler.mark_done(&task_id)?;
             }
         }
 
+        // Gates now waiting for a decision
+        for task_id in self.scheduler.take_awaiting_approval() {
+            let project = self.get_project_name(&task_id).unwrap_or_else(|| self.session.project.clone());
+            let task_display = self.get_task_display_name(&task_id);
+            self.add_recent_event(&project, format!("Awaiting approval: {}", task_display));
+            let _ = self.notification_manager.notify_waiting(&project, &task_display);
+            self.events.emit(GidEvent::GateAwaitingApproval { task_id: task_id.clone() });
+            self.session.await_approval(task_id);
+        }
+
+        // Dependents of failed tasks that will not run
+        for task_id in self.scheduler.take_skipped() {
+            let project = self.get_project_name(&task_id).unwrap_or_else(|| self.session.project.clone());
+            let task_display = self.get_task_display_name(&task_id);
+            self.add_recent_event(&project, format!("Skipped: {}", task_display));
+            self.session.skip_task(task_id);
+        }
+
         if let Err(e) = self.session.save() {
             log::warn!("Failed to save session: {}", e);
         }
@@ -275,6 +486,126 @@ impl App {
         Ok(())
     }
 
+    /// A task with its `${...}` variables resolved for this run
+    fn resolved_task(&self, task_id: &str) -> Result<Task> {
+        let task = self
+            .scheduler
+            .graph()
+            .get_task(task_id)
+            .ok_or_else(|| anyhow::anyhow!("Task not found: {}", task_id))?;
+        let project = self.port_project(task_id);
+
+        // Resolve ${vars.*}, ${env.*}, ${project.*}, ${port} and ${task.id}
+        let ctx = VarContext {
+            vars: self.scheduler.graph().vars.clone().unwrap_or_default(),
+            task_id: self.get_task_display_name(task_id),
+            project_name: Some(project.clone()),
+            project_root: match &task.root {
+                Some(root) => root.clone(),
+                None => std::env::current_dir()?,
+            },
+            port: self.port_manager.get_port(&project),
+            task_outputs: self.captured_outputs(task_id),
+        };
+        resolve_task(task, &ctx)
+    }
+
+    /// Captured values of finished tasks, as `${tasks.<id>.outputs.*}` sees them
+    fn captured_outputs(&self, task_id: &str) -> HashMap<String, HashMap<String, String>> {
+        let prefix = self.get_project_name(task_id).map(|p| format!("{}:", p));
+        let mut outputs = HashMap::new();
+        for id in self.session.tasks.keys() {
+            let Some(values) = self.session.captured(id).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            // In workspace mode, tasks of the same project use local IDs
+            if let Some(local) = prefix.as_deref().and_then(|p| id.strip_prefix(p)) {
+                outputs.insert(local.to_string(), values.clone());
+            }
+            outputs.insert(id.clone(), values.clone());
+        }
+        outputs
+    }
+
+    /// Skip a task whose inputs and outputs are unchanged since its last
+    /// successful run; returns true if it was skipped
+    fn check_cache(&mut self, task_id: &str) -> bool {
+        // Resolution errors are reported when the task is launched;
+        // services never finish, so there is nothing to cache
+        let Ok(task) = self.resolved_task(task_id) else {
+            return false;
+        };
+        if task.is_service() {
+            return false;
+        }
+        let fingerprint = match TaskCache::fingerprint(&task) {
+            Ok(Some(fingerprint)) => fingerprint,
+            Ok(None) => return false,
+            Err(e) => {
+                log::warn!("Failed to check cache for {}: {}", task_id, e);
+                return false;
+            }
+        };
+        if !self.cache.is_up_to_date(task_id, &task, &fingerprint) {
+            self.fingerprints.insert(task_id.to_string(), fingerprint);
+            return false;
+        }
+
+        log::info!("Task {} is up to date ({})", task_id, &fingerprint[..12]);
+        if let Err(e) = self.scheduler.mark_cached(task_id) {
+            log::warn!("Failed to mark task {} cached: {}", task_id, e);
+        }
+        self.session.cache_task(task_id.to_string());
+        self.session.set_captured(task_id, self.cache.captured(task_id));
+        let project = self.port_project(task_id);
+        let task_display = self.get_task_display_name(task_id);
+        self.add_recent_event(&project, format!("Cached: {} (up to date)", task_display));
+        true
+    }
+
+    /// Store the fingerprint of a successful run for the next one
+    fn record_cache(&mut self, task_id: &str, captured: &HashMap<String, String>) {
+        let Some(fingerprint) = self.fingerprints.remove(task_id) else {
+            return;
+        };
+        let recorded = self
+            .resolved_task(task_id)
+            .and_then(|task| self.cache.record(task_id, &task, &fingerprint, captured));
+        if let Err(e) = recorded {
+            log::warn!("Failed to update cache for {}: {}", task_id, e);
+        }
+    }
+
+    /// Spawn a task's command in the executor
+    async fn launch_task(&mut self, task_id: &str) -> Result<()> {
+        let task = self.resolved_task(task_id)?;
+        let command = task
+            .command
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Task {} has no command", task_id))?;
+
+        let mut options = task.spawn_options()?;
+        // Inject the project's allocated port ($PORT, $GIDTERM_PORT);
+        // explicit task env wins
+        let project = self.port_project(task_id);
+        for (key, value) in self.port_manager.get_env(&project) {
+            options.env.entry(key).or_insert(value);
+        }
+
+        let capture = OutputCapture::new(&task)?;
+        options.env.insert(
+            OUTPUT_FILE_ENV.to_string(),
+            capture.file().display().to_string(),
+        );
+        self.executor.start_task(task_id, command, options).await?;
+
+        self.captures.insert(task_id.to_string(), capture);
+        if let Some(commands) = task.semantic_commands {
+            self.semantic_commands.insert(task_id.to_string(), commands);
+        }
+        Ok(())
+    }
+
     /// Process events from executor
     pub fn process_events(&mut self) {
         let mut session_updated = false;
@@ -288,10 +619,41 @@ impl App {
         }
 
         while let Ok(event) = self.event_rx.try_recv() {
+            // A run stopped after a file change: its task is already queued again
+            match &event {
+                TaskEvent::Completed { task_id, .. }
+                | TaskEvent::Failed { task_id, .. }
+                | TaskEvent::TimedOut { task_id, .. }
+                    if self.superseded.remove(task_id) =>
+                {
+                    let exit_code = match &event {
+                        TaskEvent::Completed { exit_code, .. } => Some(*exit_code),
+                        TaskEvent::Failed { exit_code, .. } => *exit_code,
+                        _ => None,
+                    };
+                    self.session.end_task(task_id, TaskStatus::Superseded, exit_code);
+                    self.captures.remove(task_id);
+                    self.release_port(task_id);
+                    session_updated = true;
+                    continue;
+                }
+                TaskEvent::Ready { task_id } if self.superseded.contains(task_id) => continue,
+                _ => {}
+            }
+
             match event {
-                TaskEvent::Started { task_id } => {
-                    log::info!("Task started: {}", task_id);
+                TaskEvent::Started { task_id, pid } => {
+                    log::info!("Task started: {} (pid: {:?})", task_id, pid);
                     self.task_start_times.insert(task_id.clone(), Instant::now());
+
+                    // Mark the project's port active with this process
+                    let port_project = self.port_project(&task_id);
+                    if self.port_manager.get_port(&port_project).is_some() {
+                        if let Err(e) = self.port_manager.activate(&port_project, pid) {
+                            log::warn!("Failed to activate port for {}: {}", port_project, e);
+                        }
+                        self.port_holders.insert(port_project, task_id.clone());
+                    }
                     
                     // Add recent event
                     let project = self.get_project_name(&task_id).unwrap_or_else(|| self.session.project.clone());
@@ -315,6 +677,10 @@ impl App {
                             lines.drain(0..drain_count);
                         }
 
+                        if let Some(capture) = self.captures.get_mut(&task_id) {
+                            capture.scan_line(&line);
+                        }
+
                         // Track in session
                         self.session.add_output(&task_id, line.clone());
                         session_updated = true;
@@ -332,11 +698,27 @@ impl App {
                 }
                 TaskEvent::Completed { task_id, exit_code } => {
                     log::info!("Task completed: {} (exit: {})", task_id, exit_code);
+                    self.session.end_task(&task_id, TaskStatus::Done, Some(exit_code));
+                    let captured = self
+                        .captures
+                        .remove(&task_id)
+                        .map(OutputCapture::finish)
+                        .unwrap_or_default();
+                    session_updated = true;
+                    self.release_port(&task_id);
+
+                    let stopped = self.stopped_tasks.remove(&task_id);
+                    let reason = format!("exit code {}", exit_code);
+                    if !stopped && self.schedule_restart(&task_id, &reason, true) {
+                        self.session.set_captured(&task_id, captured);
+                        continue;
+                    }
+
                     if let Err(e) = self.scheduler.mark_done(&task_id) {
                         log::warn!("Failed to mark task {} done: {}", task_id, e);
                     }
-                    self.session.end_task(&task_id, TaskStatus::Done, Some(exit_code));
-                    session_updated = true;
+                    self.record_cache(&task_id, &captured);
+                    self.session.set_captured(&task_id, captured);
                     
                     // Add recent event and send notification
                     let project = self.get_project_name(&task_id).unwrap_or_else(|| self.session.project.clone());
@@ -345,24 +727,25 @@ impl App {
                     
                     self.add_recent_event(&project, format!("Completed: {}", task_display));
                     let _ = self.notification_manager.notify_complete(&project, &task_display, duration);
-                    
-                    // Deactivate port if this was the main task
-                    let _ = self.port_manager.deactivate(&project);
                 }
-                TaskEvent::Failed { task_id, error } => {
-                    log::warn!("Task failed: {} - {}", task_id, error);
-                    if let Err(e) = self.scheduler.mark_failed(&task_id) {
-                        log::warn!("Failed to mark task {} failed: {}", task_id, e);
-                    }
-                    self.session.end_task(&task_id, TaskStatus::Failed, None);
+                TaskEvent::Failed { task_id, error, exit_code } => {
+                    self.handle_task_failed(&task_id, &error, TaskStatus::Failed, exit_code);
                     session_updated = true;
-                    
-                    // Add recent event and send notification
+                }
+                TaskEvent::TimedOut { task_id, timeout } => {
+                    let error = format!("Timed out after {}", HumanDuration::from(timeout));
+                    self.handle_task_failed(&task_id, &error, TaskStatus::TimedOut, None);
+                    session_updated = true;
+                }
+                TaskEvent::Ready { task_id } => {
+                    log::info!("Service ready: {}", task_id);
+                    if let Err(e) = self.scheduler.mark_ready(&task_id) {
+                        log::warn!("Failed to mark service {} ready: {}", task_id, e);
+                    }
+
                     let project = self.get_project_name(&task_id).unwrap_or_else(|| self.session.project.clone());
                     let task_display = self.get_task_display_name(&task_id);
-                    
-                    self.add_recent_event(&project, format!("Failed: {} - {}", task_display, &error));
-                    let _ = self.notification_manager.notify_error(&project, &task_display, &error);
+                    self.add_recent_event(&project, format!("Ready: {}", task_display));
                 }
             }
         }
@@ -376,6 +759,300 @@ impl App {
         self.last_update = Instant::now();
     }
     
+    /// Record a failed task: scheduler, session, recent events and notification
+    ///
+    /// `status` is the session outcome (`Failed` or `TimedOut`); the graph
+    /// status is `Failed` either way. If the task's retry or restart policy
+    /// allows it, a retry or restart is queued instead and the task stays in
+    /// progress.
+    fn handle_task_failed(&mut self, task_id: &str, error: &str, status: TaskStatus, exit_code: Option<i32>) {
+        log::warn!("Task failed: {} - {}", task_id, error);
+        self.session.end_task(task_id, status, exit_code);
+        self.captures.remove(task_id);
+        self.release_port(task_id);
+
+        let project = self.get_project_name(task_id).unwrap_or_else(|| self.session.project.clone());
+        let task_display = self.get_task_display_name(task_id);
+
+        // Retry or restart unless the user stopped the task
+        let stopped = self.stopped_tasks.remove(task_id);
+        let attempt = self.session.last_run_attempt(task_id).unwrap_or(1);
+        let policy = self
+            .scheduler
+            .graph()
+            .get_task(task_id)
+            .map(RetryPolicy::from_task);
+        if let Some(policy) = policy {
+            if !stopped && policy.should_retry(attempt, exit_code) {
+                let delay = policy.delay_after(attempt);
+                self.pending_retries
+                    .insert(task_id.to_string(), (Instant::now() + delay, attempt + 1));
+                self.add_recent_event(
+                    &project,
+                    format!(
+                        "Retrying: {} in {} (attempt {}/{}) - {}",
+                        task_display,
+                        HumanDuration::from(delay),
+                        attempt + 1,
+                        policy.max_attempts(),
+                        error
+                    ),
+                );
+                return;
+            }
+        }
+
+        if !stopped && self.schedule_restart(task_id, error, false) {
+            return;
+        }
+
+        if let Err(e) = self.scheduler.mark_failed(task_id) {
+            log::warn!("Failed to mark task {} failed: {}", task_id, e);
+        }
+        self.fingerprints.remove(task_id);
+
+        // Add recent event and send notification
+
+        self.add_recent_event(&project, format!("Failed: {} - {}", task_display, error));
+        let _ = self.notification_manager.notify_error(&project, &task_display, error);
+    }
+
+    /// Watch the `watch:` globs of every task; returns how many are watched
+    pub fn enable_watch(&mut self) -> Result<usize> {
+        let mut watcher = FileWatcher::new();
+        let mut task_ids: Vec<String> = self.scheduler.graph().all_tasks().keys().cloned().collect();
+        task_ids.sort();
+        let mut count = 0;
+        for task_id in task_ids {
+            let task = self.scheduler.graph().get_task(&task_id).unwrap();
+            if task.watch.is_none() {
+                continue;
+            }
+            // Outputs of upstream tasks are not known yet; watch globs rarely use them
+            let task = self.resolved_task(&task_id).unwrap_or_else(|_| task.clone());
+            watcher.watch(&task_id, &task)?;
+            count += 1;
+        }
+        self.watcher = Some(watcher);
+        Ok(count)
+    }
+
+    /// Reload the graph when its file(s) change
+    pub fn enable_reload(&mut self, source: GraphSource) -> Result<()> {
+        let mut watcher = FileWatcher::new();
+        watcher.watch_files("graph", &source.files(self.scheduler.graph()))?;
+        self.graph_watcher = Some(watcher);
+        self.graph_source = Some(source);
+        Ok(())
+    }
+
+    /// Reload the graph if its file(s) changed
+    pub fn poll_reload(&mut self) {
+        let changed = match &mut self.graph_watcher {
+            Some(watcher) => !watcher.poll().is_empty(),
+            None => false,
+        };
+        if changed {
+            self.reload_graph();
+        }
+    }
+
+    /// Load the graph again and apply it without touching running tasks
+    ///
+    /// If it fails to load or validate, the error is shown and the current
+    /// graph stays in use.
+    pub fn reload_graph(&mut self) {
+        let Some(source) = self.graph_source.clone() else {
+            return;
+        };
+        let project = self.session.project.clone();
+        let loaded = source.load().and_then(|graph| match &self.selection {
+            Some(selection) => graph.select(selection),
+            None => Ok(graph),
+        });
+        let graph = match loaded {
+            Ok(graph) => graph,
+            Err(e) => {
+                log::warn!("Graph reload failed: {}", e);
+                self.add_recent_event(&project, "Reload failed".to_string());
+                self.reload_banner = Some(ReloadBanner {
+                    message: format!("Reload failed: {}", e),
+                    is_error: true,
+                    at: Instant::now(),
+                });
+                return;
+            }
+        };
+
+        let diff = self.scheduler.apply_graph(graph);
+        log::info!("Graph reloaded: {}", diff);
+        let had_error = self.reload_banner.as_ref().is_some_and(|b| b.is_error);
+        if diff.is_empty() && !had_error {
+            // e.g. only statuses changed, as written by --write-back
+            return;
+        }
+        for id in &diff.removed {
+            self.task_outputs.remove(id);
+            self.task_metrics.remove(id);
+            self.metric_history.remove(id);
+            self.advisories.remove(id);
+            self.restarts.remove(id);
+        }
+        self.selected_task = self
+            .selected_task
+            .min(self.get_task_ids().len().saturating_sub(1));
+
+        // Included files and watch globs may have changed
+        if let Err(e) = self.enable_reload(source) {
+            log::warn!("Failed to watch graph files: {}", e);
+        }
+        if self.watcher.is_some() {
+            if let Err(e) = self.enable_watch() {
+                log::warn!("Failed to watch task files: {}", e);
+            }
+        }
+
+        self.add_recent_event(&project, format!("Reloaded graph: {}", diff));
+        self.reload_banner = Some(ReloadBanner {
+            message: format!("Reloaded graph: {}", diff),
+            is_error: false,
+            at: Instant::now(),
+        });
+    }
+
+    /// Banner for the last reload; errors stay until the next good reload
+    pub fn reload_banner(&self) -> Option<&ReloadBanner> {
+        self.reload_banner
+            .as_ref()
+            .filter(|b| b.is_error || b.at.elapsed() < RELOAD_BANNER_DURATION)
+    }
+
+    /// Re-run tasks whose watched files changed
+    pub fn poll_watch(&mut self) {
+        let changed = match &mut self.watcher {
+            Some(watcher) => watcher.poll(),
+            None => return,
+        };
+        for task_id in changed {
+            if let Err(e) = self.rerun_task(&task_id) {
+                log::warn!("Failed to re-run {}: {}", task_id, e);
+            }
+        }
+    }
+
+    /// Queue a task and its dependents to run again, stopping any that are
+    /// running; their previous runs stay in the session
+    pub fn rerun_task(&mut self, task_id: &str) -> Result<()> {
+        let requeued = self.scheduler.requeue(task_id)?;
+        if requeued.is_empty() {
+            // Not started yet: it will see the change when it runs
+            return Ok(());
+        }
+
+        for id in &requeued {
+            self.pending_retries.remove(id);
+            self.pending_restarts.remove(id);
+            self.fingerprints.remove(id);
+            self.task_outputs.remove(id);
+            self.task_metrics.remove(id);
+            self.metric_history.remove(id);
+            if self.executor.is_running(id) {
+                self.superseded.insert(id.clone());
+                if let Err(e) = self.executor.stop_task(id) {
+                    log::warn!("Failed to stop task {}: {}", id, e);
+                }
+            }
+        }
+
+        let project = self.get_project_name(task_id).unwrap_or_else(|| self.session.project.clone());
+        let task_display = self.get_task_display_name(task_id);
+        let message = match requeued.len() - 1 {
+            0 => format!("Changed: {} - re-running", task_display),
+            n => format!("Changed: {} - re-running with {} dependent(s)", task_display, n),
+        };
+        self.add_recent_event(&project, message);
+        Ok(())
+    }
+
+    /// Queue a restart of a task whose process exited, if its restart policy
+    /// allows it; returns false if the task stays down
+    ///
+    /// Every exit is recorded, so the dashboard can show the last reason and
+    /// the advisor can spot a crash loop.
+    fn schedule_restart(&mut self, task_id: &str, reason: &str, succeeded: bool) -> bool {
+        let Some(policy) = self.scheduler.graph().get_task(task_id).map(RestartPolicy::from_task) else {
+            return false;
+        };
+        if policy.mode == RestartMode::Never {
+            return false;
+        }
+
+        let uptime = self.task_start_times.get(task_id).map(|t| t.elapsed());
+        let history = self.restarts.entry(task_id.to_string()).or_default();
+        let step = history.record_exit(reason.to_string(), uptime);
+        let restart = policy.should_restart(history.count, succeeded);
+        if restart {
+            history.count += 1;
+        }
+        let count = history.count;
+        self.update_crash_advisory(task_id);
+        if !restart {
+            return false;
+        }
+
+        let delay = policy.delay_after(step);
+        self.pending_restarts.insert(task_id.to_string(), Instant::now() + delay);
+
+        let project = self.get_project_name(task_id).unwrap_or_else(|| self.session.project.clone());
+        let task_display = self.get_task_display_name(task_id);
+        let limit = policy.max_restarts.map(|max| format!("/{}", max)).unwrap_or_default();
+        self.add_recent_event(
+            &project,
+            format!(
+                "Restarting: {} in {} (restart {}{}) - {}",
+                task_display,
+                HumanDuration::from(delay),
+                count,
+                limit,
+                reason
+            ),
+        );
+        true
+    }
+
+    /// Replace a task's crash-loop advisory with a fresh evaluation
+    fn update_crash_advisory(&mut self, task_id: &str) {
+        let crash_loop = self.crash_advisory(task_id);
+        let advisories = self.advisories.entry(task_id.to_string()).or_default();
+        advisories.retain(|a| !a.is_crash_loop());
+        advisories.extend(crash_loop);
+        if advisories.is_empty() {
+            self.advisories.remove(task_id);
+        }
+    }
+
+    fn crash_advisory(&self, task_id: &str) -> Option<Advisory> {
+        let history = self.restarts.get(task_id)?;
+        self.advisor
+            .evaluate_crashes(&history.exits, history.last_reason.as_deref())
+    }
+
+    /// Project whose port a task uses (the session project in single mode)
+    fn port_project(&self, task_id: &str) -> String {
+        self.get_project_name(task_id).unwrap_or_else(|| self.session.project.clone())
+    }
+
+    /// Deactivate the project's port if this task was holding it
+    fn release_port(&mut self, task_id: &str) {
+        let project = self.port_project(task_id);
+        if self.port_holders.get(&project).map(|t| t == task_id).unwrap_or(false) {
+            self.port_holders.remove(&project);
+            if let Err(e) = self.port_manager.deactivate(&project) {
+                log::warn!("Failed to deactivate port for {}: {}", project, e);
+            }
+        }
+    }
+
     /// Add a recent event (keeps last 50)
     fn add_recent_event(&mut self, project: &str, message: String) {
         self.recent_events.push((Instant::now(), project.to_string(), message));
@@ -387,7 +1064,7 @@ impl App {
     /// Get task display name (strip project prefix in workspace mode)
     pub fn get_task_display_name(&self, task_id: &str) -> String {
         if self.workspace_mode {
-            task_id.split(':').nth(1).unwrap_or(task_id).to_string()
+            task_id.split_once(':').map(|(_, t)| t).unwrap_or(task_id).to_string()
         } else {
             task_id.to_string()
         }
@@ -462,8 +1139,9 @@ impl App {
 
                 // Run advisor
                 let history_ref = self.metric_history.get(task_id);
-                let new_advisories = self.advisor.evaluate(&metrics, history_ref);
+                let mut new_advisories = self.advisor.evaluate(&metrics, history_ref);
                 if !new_advisories.is_empty() {
+                    new_advisories.extend(self.crash_advisory(task_id));
                     self.advisories.insert(task_id.to_string(), new_advisories);
                 }
 
@@ -472,6 +1150,210 @@ impl App {
         }
     }
 
+    /// Approve a gate awaiting approval, releasing its dependents
+    pub fn approve_gate(&mut self, task_id: &str, approver: &str, reason: Option<String>) -> Result<()> {
+        self.decide_gate(task_id, true, approver, reason)
+    }
+
+    /// Reject a gate awaiting approval; dependents that need it are skipped
+    pub fn reject_gate(&mut self, task_id: &str, approver: &str, reason: Option<String>) -> Result<()> {
+        self.decide_gate(task_id, false, approver, reason)
+    }
+
+    fn decide_gate(&mut self, task_id: &str, approved: bool, approver: &str, reason: Option<String>) -> Result<()> {
+        if approved {
+            self.scheduler.approve(task_id)?;
+        } else {
+            self.scheduler.reject(task_id)?;
+        }
+        log::info!(
+            "Gate {} {} by {}",
+            task_id,
+            if approved { "approved" } else { "rejected" },
+            approver
+        );
+
+        let project = self.get_project_name(task_id).unwrap_or_else(|| self.session.project.clone());
+        let task_display = self.get_task_display_name(task_id);
+        let verb = if approved { "Approved" } else { "Rejected" };
+        let message = match &reason {
+            Some(reason) => format!("{}: {} by {} ({})", verb, task_display, approver, reason),
+            None => format!("{}: {} by {}", verb, task_display, approver),
+        };
+        self.add_recent_event(&project, message);
+
+        self.events.emit(GidEvent::GateDecided {
+            task_id: task_id.to_string(),
+            approved,
+            approver: approver.to_string(),
+            reason: reason.clone(),
+        });
+        self.session.record_approval(
+            task_id,
+            Approval {
+                approved,
+                approver: approver.to_string(),
+                reason,
+                at: Utc::now(),
+            },
+        );
+        if let Err(e) = self.session.save() {
+            log::warn!("Failed to save session: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Submit the approval prompt, deciding the gate as the local user
+    fn submit_approval_prompt(&mut self) {
+        let Some(prompt) = self.approval_prompt.take() else {
+            return;
+        };
+        let reason = Some(prompt.reason.trim().to_string()).filter(|r| !r.is_empty());
+        let approver = local_user();
+        let result = if prompt.approve {
+            self.approve_gate(&prompt.task_id, &approver, reason)
+        } else {
+            self.reject_gate(&prompt.task_id, &approver, reason)
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to decide gate {}: {}", prompt.task_id, e);
+        }
+    }
+
+    /// Carry out a command from a control mode (MCP tool call, agent, ...)
+    ///
+    /// Gate decisions without an `approver` are recorded under the mode's
+    /// name, or the local user in manual mode. Ready tasks start on their
+    /// own, so `start_all` has nothing to do and `start_task` re-runs a task.
+    pub fn handle_command(&mut self, command: ControlCommand, mode: ControlMode) -> ControlResponse {
+        let approver = |approver: Option<String>| {
+            approver.unwrap_or_else(|| match mode {
+                ControlMode::Manual => local_user(),
+                ControlMode::Mcp => "mcp".to_string(),
+                ControlMode::Agent => "agent".to_string(),
+            })
+        };
+        let result = match command {
+            ControlCommand::StartAll => Ok(None),
+            ControlCommand::StartTask { task_id } => self.rerun_task(&task_id).map(|_| None),
+            ControlCommand::StopTask { task_id } => {
+                self.stopped_tasks.insert(task_id.clone());
+                self.executor.stop_task(&task_id).map(|_| None)
+            }
+            ControlCommand::SendInput { task_id, input } => self.executor.send_input(&task_id, &input).map(|_| None),
+            ControlCommand::ApproveGate { task_id, approver: name, reason } => {
+                self.approve_gate(&task_id, &approver(name), reason).map(|_| None)
+            }
+            ControlCommand::RejectGate { task_id, approver: name, reason } => {
+                self.reject_gate(&task_id, &approver(name), reason).map(|_| None)
+            }
+            ControlCommand::GetState => serde_json::to_value(self.state_snapshot()).map(Some).map_err(Into::into),
+            ControlCommand::GetOutput { task_id, lines } => {
+                serde_json::to_value(self.get_task_output(&task_id, lines)).map(Some).map_err(Into::into)
+            }
+            ControlCommand::Quit => {
+                self.should_quit = true;
+                Ok(None)
+            }
+        };
+        match result {
+            Ok(Some(data)) => ControlResponse::ok_with_data(data),
+            Ok(None) => ControlResponse::ok(),
+            Err(e) => ControlResponse::error(e.to_string()),
+        }
+    }
+
+    /// Current state of every task, for control-mode consumers
+    pub fn state_snapshot(&self) -> StateSnapshot {
+        let graph = self.scheduler.graph();
+        let tasks: Vec<TaskSnapshot> = self
+            .get_task_ids()
+            .into_iter()
+            .filter_map(|id| {
+                let task = graph.get_task(&id)?;
+                let metrics = self.task_metrics.get(&id);
+                Some(TaskSnapshot {
+                    status: task.status.to_string(),
+                    description: task.description.clone(),
+                    progress: metrics.map(|m| m.progress as f64),
+                    metrics: metrics.map(metrics_to_json),
+                    last_output: self.get_task_output(&id, 10),
+                    id,
+                })
+            })
+            .collect();
+        let count = |status: GraphTaskStatus| graph.all_tasks().values().filter(|t| t.status == status).count();
+        StateSnapshot {
+            running_count: count(GraphTaskStatus::InProgress),
+            done_count: count(GraphTaskStatus::Done),
+            failed_count: count(GraphTaskStatus::Failed),
+            total_count: tasks.len(),
+            tasks,
+        }
+    }
+
+    /// Continue a previous session instead of starting a new one
+    ///
+    /// Tasks whose last run succeeded (or was cached) stay finished; tasks
+    /// that were running, failed or skipped run again. New runs are added to
+    /// the resumed session. Returns the restored and re-queued task IDs.
+    pub fn resume(&mut self, mut session: Session) -> Result<(Vec<String>, Vec<String>)> {
+        if session.project != self.session.project {
+            log::warn!(
+                "Resuming session {} of project '{}' in project '{}'",
+                session.id,
+                session.project,
+                self.session.project
+            );
+        }
+        session.resume();
+
+        let mut task_ids: Vec<String> = self.scheduler.graph().all_tasks().keys().cloned().collect();
+        task_ids.sort();
+        let mut restored = Vec::new();
+        let mut requeued = Vec::new();
+        for task_id in task_ids {
+            let Some(run) = session.tasks.get(&task_id).and_then(|h| h.runs.last()) else {
+                continue;
+            };
+            let status = match run.status {
+                TaskStatus::Done | TaskStatus::Approved => GraphTaskStatus::Done,
+                TaskStatus::Cached => GraphTaskStatus::Cached,
+                _ => GraphTaskStatus::Pending,
+            };
+            if status == GraphTaskStatus::Pending {
+                requeued.push(task_id.clone());
+            } else {
+                self.task_outputs.insert(task_id.clone(), run.output.clone());
+                restored.push(task_id.clone());
+            }
+            self.scheduler.restore(&task_id, status)?;
+        }
+
+        log::info!(
+            "Resumed session {}: {} task(s) finished, {} to run again",
+            session.id,
+            restored.len(),
+            requeued.len()
+        );
+        self.add_recent_event(&session.project.clone(), format!("Resumed session {}", session.id));
+        self.session = session;
+        Ok((restored, requeued))
+    }
+
+    /// Only run the selected tasks and what they depend on; returns how
+    /// many tasks are left
+    ///
+    /// Call before the run starts (and before enabling write-back).
+    pub fn select_tasks(&mut self, selection: TaskSelection) -> Result<usize> {
+        let graph = self.scheduler.graph().select(&selection)?;
+        let count = graph.tasks.len();
+        self.scheduler = Scheduler::new(graph);
+        self.selected_task = 0;
+        self.selection = Some(selection);
+        Ok(count)
+    }
+
     /// Get advisories for a task
     pub fn get_advisories(&self, task_id: &str) -> Option<&Vec<Advisory>> {
         self.advisories.get(task_id)
@@ -491,6 +1373,20 @@ impl App {
 
     /// Handle keyboard input
     pub fn handle_key(&mut self, key: KeyEvent) {
+        // Reason for a gate decision
+        if let Some(prompt) = &mut self.approval_prompt {
+            match key.code {
+                KeyCode::Esc => self.approval_prompt = None,
+                KeyCode::Enter => self.submit_approval_prompt(),
+                KeyCode::Backspace => {
+                    prompt.reason.pop();
+                }
+                KeyCode::Char(c) => prompt.reason.push(c),
+                _ => {}
+            }
+            return;
+        }
+
         // Handle search mode input
         if self.search_mode {
             match key.code {
@@ -534,9 +1430,45 @@ impl App {
             KeyCode::Char('k') => {
                 // Kill selected task
                 let task_ids = self.get_task_ids();
+                if let Some(task_id) = task_ids.get(self.selected_task).cloned() {
+                    if self.pending_retries.remove(&task_id).is_some() {
+                        // Waiting to retry: give up now
+                        if let Err(e) = self.scheduler.mark_failed(&task_id) {
+                            log::warn!("Failed to mark task {} failed: {}", task_id, e);
+                        }
+                    } else if self.pending_restarts.remove(&task_id).is_some() {
+                        // Waiting to restart: stay down, as the last run ended
+                        let result = if self.session.last_run_status(&task_id) == Some(&TaskStatus::Done) {
+                            self.scheduler.mark_done(&task_id)
+                        } else {
+                            self.scheduler.mark_failed(&task_id)
+                        };
+                        if let Err(e) = result {
+                            log::warn!("Failed to stop restarting task {}: {}", task_id, e);
+                        }
+                    } else if self.executor.is_running(&task_id) {
+                        self.stopped_tasks.insert(task_id.clone());
+                        if let Err(e) = self.executor.stop_task(&task_id) {
+                            log::warn!("Failed to stop task {}: {}", task_id, e);
+                        }
+                    }
+                }
+            }
+            KeyCode::Char(c @ ('a' | 'x')) if self.view_mode == ViewMode::Dashboard => {
+                // Approve or reject the selected gate, asking for a reason
+                let task_ids = self.get_task_ids();
                 if let Some(task_id) = task_ids.get(self.selected_task) {
-                    if let Err(e) = self.executor.stop_task(task_id) {
-                        log::warn!("Failed to stop task {}: {}", task_id, e);
+                    let awaiting = self
+                        .scheduler
+                        .graph()
+                        .get_task(task_id)
+                        .is_some_and(|t| t.status == GraphTaskStatus::AwaitingApproval);
+                    if awaiting {
+                        self.approval_prompt = Some(ApprovalPrompt {
+                            task_id: task_id.clone(),
+                            approve: c == 'a',
+                            reason: String::new(),
+                        });
                     }
                 }
             }
@@ -700,7 +1632,10 @@ impl App {
     /// Get semantic commands for a task (from graph YAML semantic_commands field)
     pub fn get_semantic_commands(&self, task_id: &str) -> Option<TaskCommands> {
         let task = self.scheduler.graph().get_task(task_id)?;
-        let map = task.semantic_commands.as_ref()?;
+        let map = self
+            .semantic_commands
+            .get(task_id)
+            .or(task.semantic_commands.as_ref())?;
         if map.is_empty() {
             return None;
         }
@@ -808,6 +1743,17 @@ impl App {
                 .rev()
                 .find(|(_, p, _)| p == name)
                 .map(|(_, _, msg)| msg.clone());
+
+            // Cross-project edges
+            let mut depends_on_projects: Vec<String> = task_ids
+                .iter()
+                .filter_map(|id| graph.get_task(id))
+                .flat_map(|task| task.depends_on.iter().flatten())
+                .filter_map(|dep| self.get_project_name(dep))
+                .filter(|project| project != name)
+                .collect();
+            depends_on_projects.sort();
+            depends_on_projects.dedup();
             
             summaries.push(ProjectSummary {
                 name: name.clone(),
@@ -818,6 +1764,7 @@ impl App {
                 tasks_running,
                 tasks_failed,
                 recent_event,
+                depends_on_projects,
             });
         }
         
@@ -878,3 +1825,10 @@ impl App {
         self.agent_manager.get_status(project).emoji()
     }
 }
+
+/// Name recorded as the approver of decisions made in the TUI
+fn local_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
@@ -0,0 +1,287 @@
+//! Incremental execution - skip tasks whose inputs and outputs are unchanged
+//!
+//! ```yaml
+//! build:
+//!   command: cargo build --release
+//!   inputs: [src/**/*.rs, Cargo.toml]
+//!   outputs: [target/release/gidterm]
+//! ```
+//!
+//! Before a task with `inputs:` runs, it is fingerprinted: a SHA-256 over its
+//! command, environment and the content of every file matching the input
+//! globs. After a successful run the fingerprint and the hashes of the
+//! output files are stored under `.gidterm/cache/`. The task is up to date
+//! while the fingerprint matches and every output is still there, unchanged.
+
+use super::Task;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cache directory, relative to where gidterm runs (next to the sessions)
+pub const CACHE_DIR: &str = ".gidterm/cache";
+
+/// What was recorded after the last successful run of a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    task_id: String,
+    fingerprint: String,
+    /// Output file (relative to the task directory) -> content hash
+    outputs: BTreeMap<String, String>,
+    /// Values captured for dependents, replayed when the task is cached
+    #[serde(default)]
+    captured: HashMap<String, String>,
+    completed_at: DateTime<Utc>,
+}
+
+/// Fingerprints and output hashes of successful runs, one file per task
+#[derive(Debug, Clone)]
+pub struct TaskCache {
+    dir: PathBuf,
+}
+
+impl Default for TaskCache {
+    fn default() -> Self {
+        Self::new(CACHE_DIR)
+    }
+}
+
+impl TaskCache {
+    /// Cache stored in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Fingerprint of a task's inputs, or `None` if it declares no `inputs`
+    ///
+    /// Pass the task with its variables already resolved.
+    pub fn fingerprint(task: &Task) -> Result<Option<String>> {
+        let Some(inputs) = &task.inputs else {
+            return Ok(None);
+        };
+        let options = task.spawn_options()?;
+        let base = task_dir(options.cwd.as_deref())?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(task.command.as_deref().unwrap_or("").as_bytes());
+        hasher.update([0]);
+        for (key, value) in options.env.iter().collect::<BTreeMap<_, _>>() {
+            hasher.update(format!("{}={}", key, value).as_bytes());
+            hasher.update([0]);
+        }
+        for (path, hash) in hash_files(&base, inputs)? {
+            hasher.update(path.as_bytes());
+            hasher.update([0]);
+            hasher.update(hash.as_bytes());
+            hasher.update([0]);
+        }
+        Ok(Some(format!("{:x}", hasher.finalize())))
+    }
+
+    /// True if the last successful run had this fingerprint and its
+    /// outputs are unchanged
+    pub fn is_up_to_date(&self, task_id: &str, task: &Task, fingerprint: &str) -> bool {
+        let Some(entry) = self.load(task_id) else {
+            return false;
+        };
+        if entry.fingerprint != fingerprint {
+            return false;
+        }
+
+        let outputs = task.outputs.clone().unwrap_or_default();
+        match current_outputs(task, &outputs) {
+            Ok(Some(current)) => current == entry.outputs,
+            Ok(None) => false,
+            Err(e) => {
+                log::debug!("Failed to hash outputs of {}: {}", task_id, e);
+                false
+            }
+        }
+    }
+
+    /// Values captured during the cached run
+    pub fn captured(&self, task_id: &str) -> HashMap<String, String> {
+        self.load(task_id).map(|e| e.captured).unwrap_or_default()
+    }
+
+    /// Record a successful run
+    pub fn record(
+        &self,
+        task_id: &str,
+        task: &Task,
+        fingerprint: &str,
+        captured: &HashMap<String, String>,
+    ) -> Result<()> {
+        let outputs = task.outputs.clone().unwrap_or_default();
+        let Some(outputs) = current_outputs(task, &outputs)? else {
+            // Declared outputs were not produced; nothing worth caching
+            self.remove(task_id);
+            return Ok(());
+        };
+
+        let entry = CacheEntry {
+            task_id: task_id.to_string(),
+            fingerprint: fingerprint.to_string(),
+            outputs,
+            captured: captured.clone(),
+            completed_at: Utc::now(),
+        };
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(task_id), serde_json::to_string_pretty(&entry)?)?;
+        Ok(())
+    }
+
+    /// Forget a task, so it runs next time
+    pub fn remove(&self, task_id: &str) {
+        let _ = fs::remove_file(self.entry_path(task_id));
+    }
+
+    fn load(&self, task_id: &str) -> Option<CacheEntry> {
+        let content = fs::read_to_string(self.entry_path(task_id)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+        (entry.task_id == task_id).then_some(entry)
+    }
+
+    /// Task IDs may contain `:`, `/` or `[...]`, so files are named by hash
+    fn entry_path(&self, task_id: &str) -> PathBuf {
+        let hash = format!("{:x}", Sha256::digest(task_id.as_bytes()));
+        self.dir.join(format!("{}.json", &hash[..16]))
+    }
+}
+
+/// Directory globs are relative to: the task's cwd, else the current directory
+pub(super) fn task_dir(cwd: Option<&Path>) -> Result<PathBuf> {
+    Ok(match cwd {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir()?,
+    })
+}
+
+/// Hashes of the task's outputs, or `None` if a pattern matches nothing
+fn current_outputs(task: &Task, patterns: &[String]) -> Result<Option<BTreeMap<String, String>>> {
+    let base = task_dir(task.spawn_options()?.cwd.as_deref())?;
+    for pattern in patterns {
+        if expand(&base, pattern)?.is_empty() {
+            return Ok(None);
+        }
+    }
+    Ok(Some(hash_files(&base, patterns)?))
+}
+
+/// Content hash of every file matching the patterns, keyed by relative path
+///
+/// A pattern that matches a directory includes everything below it.
+fn hash_files(base: &Path, patterns: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    for pattern in patterns {
+        for path in expand(base, pattern)? {
+            let key = path
+                .strip_prefix(base)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .into_owned();
+            if let Entry::Vacant(entry) = hashes.entry(key) {
+                let content = fs::read(&path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+                entry.insert(format!("{:x}", Sha256::digest(&content)));
+            }
+        }
+    }
+    Ok(hashes)
+}
+
+/// Files matching one glob pattern (relative to `base`), directories expanded
+pub(super) fn expand(base: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let full = if Path::new(pattern).is_absolute() {
+        pattern.to_string()
+    } else {
+        // The base may itself contain glob characters, e.g. a `[...]` task dir
+        let base = glob::Pattern::escape(&base.to_string_lossy());
+        format!("{}/{}", base.trim_end_matches('/'), pattern)
+    };
+    let paths = glob::glob(&full)
+        .map_err(|e| anyhow::anyhow!("Invalid glob pattern '{}': {}", pattern, e))?;
+
+    let mut files = Vec::new();
+    for path in paths.flatten() {
+        collect_files(&path, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            collect_files(&entry?.path(), files)?;
+        }
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(dir: &Path) -> Task {
+        Task {
+            description: "Build".into(),
+            command: Some("cat src/*.txt > out/bundle.txt".into()),
+            cwd: Some(dir.display().to_string()),
+            inputs: Some(vec!["src/*.txt".into()]),
+            outputs: Some(vec!["out/bundle.txt".into()]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_up_to_date_until_inputs_or_outputs_change() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("out")).unwrap();
+        fs::write(dir.path().join("src/a.txt"), "a").unwrap();
+        let cache = TaskCache::new(dir.path().join("cache"));
+        let task = task(dir.path());
+
+        let fingerprint = TaskCache::fingerprint(&task).unwrap().unwrap();
+        assert!(!cache.is_up_to_date("build", &task, &fingerprint));
+
+        // Missing outputs are not cached
+        cache.record("build", &task, &fingerprint, &HashMap::new()).unwrap();
+        assert!(!cache.is_up_to_date("build", &task, &fingerprint));
+
+        fs::write(dir.path().join("out/bundle.txt"), "a").unwrap();
+        cache.record("build", &task, &fingerprint, &HashMap::new()).unwrap();
+        assert!(cache.is_up_to_date("build", &task, &fingerprint));
+
+        // Touched output
+        fs::write(dir.path().join("out/bundle.txt"), "edited").unwrap();
+        assert!(!cache.is_up_to_date("build", &task, &fingerprint));
+        cache.record("build", &task, &fingerprint, &HashMap::new()).unwrap();
+
+        // New input file
+        fs::write(dir.path().join("src/b.txt"), "b").unwrap();
+        let changed = TaskCache::fingerprint(&task).unwrap().unwrap();
+        assert_ne!(changed, fingerprint);
+        assert!(!cache.is_up_to_date("build", &task, &changed));
+    }
+
+    #[test]
+    fn test_fingerprint_covers_command() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut task = task(dir.path());
+        let before = TaskCache::fingerprint(&task).unwrap();
+        task.command = Some("cat src/*.txt | sort > out/bundle.txt".into());
+        assert_ne!(TaskCache::fingerprint(&task).unwrap(), before);
+
+        task.inputs = None;
+        assert_eq!(TaskCache::fingerprint(&task).unwrap(), None);
+    }
+}
@@ -0,0 +1,144 @@
+//! Output capture - values a task hands to its dependents
+//!
+//! ```yaml
+//! build:
+//!   command: ./build-image.sh
+//!   outputs_capture:
+//!     tag: "Successfully tagged (\\S+)"     # first group, else the whole match
+//! deploy:
+//!   command: "kubectl set image deploy/app app=${tasks.build.outputs.tag}"
+//!   depends_on: [build]
+//! ```
+//!
+//! A task can also write `key=value` lines to the file named by
+//! `$GIDTERM_OUTPUT`; those win over values captured from the output.
+
+use super::env::parse_env;
+use super::Task;
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::LazyLock;
+use tempfile::TempPath;
+
+/// Environment variable naming the file a task may write values to
+pub const OUTPUT_FILE_ENV: &str = "GIDTERM_OUTPUT";
+
+/// Terminal escape sequences (colors, cursor movement) to strip
+static ANSI: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07]*\x07|\r").unwrap());
+
+/// Collects the output values of one run of a task
+#[derive(Debug)]
+pub struct OutputCapture {
+    patterns: Vec<(String, Regex)>,
+    values: HashMap<String, String>,
+    /// Created empty with a random name; removed on drop
+    file: TempPath,
+}
+
+impl OutputCapture {
+    /// Compile the task's `outputs_capture` patterns and create an output file
+    ///
+    /// The file gets a random name and is created exclusively, readable by
+    /// the current user only, so nobody else can plant it in the shared
+    /// temp directory.
+    pub fn new(task: &Task) -> Result<Self> {
+        let mut patterns = Vec::new();
+        for (key, pattern) in task.outputs_capture.iter().flatten() {
+            let re = Regex::new(pattern)
+                .map_err(|e| anyhow::anyhow!("Invalid outputs_capture pattern for '{}': {}", key, e))?;
+            patterns.push((key.clone(), re));
+        }
+        patterns.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let file = tempfile::Builder::new()
+            .prefix("gidterm-")
+            .suffix(".out")
+            .tempfile()
+            .map_err(|e| anyhow::anyhow!("Failed to create output file: {}", e))?
+            .into_temp_path();
+
+        Ok(Self {
+            patterns,
+            values: HashMap::new(),
+            file,
+        })
+    }
+
+    /// File passed to the task as `$GIDTERM_OUTPUT`
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// Match one output line; the last match of each pattern wins
+    pub fn scan_line(&mut self, line: &str) {
+        if self.patterns.is_empty() {
+            return;
+        }
+        let line = ANSI.replace_all(line, "");
+        for (key, re) in &self.patterns {
+            if let Some(caps) = re.captures(&line) {
+                let value = caps.get(1).or_else(|| caps.get(0)).unwrap();
+                self.values.insert(key.clone(), value.as_str().trim().to_string());
+            }
+        }
+    }
+
+    /// Values captured from the output and the output file
+    pub fn finish(mut self) -> HashMap<String, String> {
+        if let Ok(content) = std::fs::read_to_string(&self.file) {
+            self.values.extend(parse_env(&content));
+        }
+        self.values // The file is removed when `self.file` drops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_from_output_and_file() {
+        let task = Task {
+            description: "Build".into(),
+            outputs_capture: Some(HashMap::from([
+                ("tag".to_string(), r"Successfully tagged (\S+)".to_string()),
+                ("version".to_string(), r"v\d+\.\d+\.\d+".to_string()),
+            ])),
+            ..Default::default()
+        };
+        let mut capture = OutputCapture::new(&task).unwrap();
+        capture.scan_line("Step 1/3 : FROM rust");
+        capture.scan_line("\x1b[32mSuccessfully tagged app:abc123\x1b[0m\r");
+        capture.scan_line("built v1.2.0 then v1.2.1");
+        std::fs::write(capture.file(), "commit=deadbeef\nversion=1.2.1\n").unwrap();
+
+        let file = capture.file().to_path_buf();
+        let values = capture.finish();
+        assert_eq!(values["tag"], "app:abc123");
+        assert_eq!(values["commit"], "deadbeef");
+        // The output file wins over captured output
+        assert_eq!(values["version"], "1.2.1");
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_output_file_is_private() {
+        let task = Task::default();
+        let first = OutputCapture::new(&task).unwrap();
+        let second = OutputCapture::new(&task).unwrap();
+        assert_ne!(first.file(), second.file());
+
+        // Created up front, so nobody else can create it (or a symlink) first
+        let metadata = std::fs::symlink_metadata(first.file()).unwrap();
+        assert!(metadata.is_file());
+        assert_eq!(metadata.len(), 0);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Human-friendly durations for graph fields (`30s`, `5m`, `1h30m`, `500ms`)
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// One `<number><unit>` part of a duration
+static PART: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d+(?:\.\d+)?)\s*(ms|s|m|h|d)").unwrap());
+
+/// A duration written as `500ms`, `30s`, `5m`, `1h30m`, `1d` or plain seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    /// Get the wrapped duration
+    pub fn as_duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(d: Duration) -> Self {
+        Self(d)
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            anyhow::bail!("empty duration");
+        }
+
+        // Plain number = seconds
+        if let Ok(secs) = s.parse::<f64>() {
+            if secs < 0.0 {
+                anyhow::bail!("negative duration: {}", s);
+            }
+            return Ok(Self(Duration::from_secs_f64(secs)));
+        }
+
+        let mut total = 0.0;
+        let mut consumed = 0;
+        for caps in PART.captures_iter(s) {
+            let whole = caps.get(0).unwrap();
+            if s[consumed..whole.start()].trim() != "" {
+                anyhow::bail!("invalid duration: {}", s);
+            }
+            consumed = whole.end();
+
+            let value: f64 = caps[1].parse()?;
+            total += value
+                * match &caps[2] {
+                    "ms" => 0.001,
+                    "s" => 1.0,
+                    "m" => 60.0,
+                    "h" => 3600.0,
+                    _ => 86400.0,
+                };
+        }
+        if consumed == 0 || s[consumed..].trim() != "" {
+            anyhow::bail!("invalid duration: {} (expected e.g. 30s, 5m, 1h30m)", s);
+        }
+
+        Ok(Self(Duration::from_secs_f64(total)))
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self.0.as_millis();
+        if !millis.is_multiple_of(1000) {
+            return write!(f, "{}ms", millis);
+        }
+
+        let secs = self.0.as_secs();
+        let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+        let mut out = String::new();
+        if h > 0 {
+            out.push_str(&format!("{}h", h));
+        }
+        if m > 0 {
+            out.push_str(&format!("{}m", m));
+        }
+        if s > 0 || out.is_empty() {
+            out.push_str(&format!("{}s", s));
+        }
+        write!(f, "{}", out)
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Secs(u64),
+            Float(f64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Secs(secs) => Ok(Self(Duration::from_secs(secs))),
+            Raw::Float(secs) if secs >= 0.0 => Ok(Self(Duration::from_secs_f64(secs))),
+            Raw::Float(secs) => Err(serde::de::Error::custom(format!(
+                "negative duration: {}",
+                secs
+            ))),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Duration {
+        s.parse::<HumanDuration>().unwrap().0
+    }
+
+    #[test]
+    fn test_parse_durations() {
+        assert_eq!(parse("30"), Duration::from_secs(30));
+        assert_eq!(parse("30s"), Duration::from_secs(30));
+        assert_eq!(parse("5m"), Duration::from_secs(300));
+        assert_eq!(parse("1h30m"), Duration::from_secs(5400));
+        assert_eq!(parse("500ms"), Duration::from_millis(500));
+        assert_eq!(parse("1.5s"), Duration::from_millis(1500));
+        assert!("abc".parse::<HumanDuration>().is_err());
+        assert!("5 minutes".parse::<HumanDuration>().is_err());
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for s in ["30s", "5m", "1h30m", "500ms", "2h"] {
+            assert_eq!(s.parse::<HumanDuration>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_number_or_string() {
+        let d: HumanDuration = serde_yaml::from_str("90").unwrap();
+        assert_eq!(d.0, Duration::from_secs(90));
+        let d: HumanDuration = serde_yaml::from_str("\"2m\"").unwrap();
+        assert_eq!(d.0, Duration::from_secs(120));
+    }
+}
@@ -0,0 +1,80 @@
+//! Environment files - parse `.env` style KEY=VALUE files for tasks
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Load an env file from disk
+pub fn load_env_file(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read env file {}: {}", path.display(), e))?;
+    Ok(parse_env(&content))
+}
+
+/// Parse `.env` content
+///
+/// Supports `KEY=VALUE`, `export KEY=VALUE`, `#` comments, blank lines and
+/// single/double quoted values. Lines without `=` are ignored.
+pub fn parse_env(content: &str) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+
+        vars.insert(key.to_string(), unquote(value.trim()));
+    }
+
+    vars
+}
+
+/// Strip matching quotes; unquoted values lose trailing ` # comments`
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+
+    match value.find(" #") {
+        Some(idx) => value[..idx].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        let vars = parse_env(
+            r#"
+# Database settings
+DATABASE_URL=postgres://localhost/dev
+export API_KEY="secret value"
+NAME='single'
+DEBUG=1 # enable debug
+not a var
+"#,
+        );
+
+        assert_eq!(vars.len(), 4);
+        assert_eq!(vars["DATABASE_URL"], "postgres://localhost/dev");
+        assert_eq!(vars["API_KEY"], "secret value");
+        assert_eq!(vars["NAME"], "single");
+        assert_eq!(vars["DEBUG"], "1");
+    }
+}
@@ -3,19 +3,37 @@
 //! Uses tokio::task::spawn_blocking for PTY reads to avoid
 //! blocking the async runtime.
 
-use super::pty::PTYHandle;
+use super::duration::HumanDuration;
+use super::pty::{ExitResult, PTYHandle, Signal, SpawnOptions};
 use anyhow::Result;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+/// Time between SIGTERM and SIGKILL when a task times out
+pub const DEFAULT_KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// How long a task may keep running after closing its output
+const EXIT_WAIT: Duration = Duration::from_secs(2);
+
 /// Task execution event
 #[derive(Debug, Clone)]
 pub enum TaskEvent {
-    Started { task_id: String },
+    Started { task_id: String, pid: Option<u32> },
     Output { task_id: String, line: String },
     Completed { task_id: String, exit_code: i32 },
-    Failed { task_id: String, error: String },
+    /// `exit_code` is set when the process exited on its own with a non-zero code
+    Failed {
+        task_id: String,
+        error: String,
+        exit_code: Option<i32>,
+    },
+    /// Task exceeded its `timeout` and was terminated
+    TimedOut { task_id: String, timeout: Duration },
+    /// Service passed its readiness probe and keeps running
+    Ready { task_id: String },
 }
 
 /// Task executor - manages running tasks
@@ -39,11 +57,16 @@ impl Executor {
     }
 
     /// Start a task
-    pub async fn start_task(&self, task_id: &str, command: &str) -> Result<()> {
+    pub async fn start_task(
+        &self,
+        task_id: &str,
+        command: &str,
+        options: SpawnOptions,
+    ) -> Result<()> {
         log::info!("Starting task: {} with command: {}", task_id, command);
 
         // Create PTY
-        let handle = PTYHandle::spawn(task_id, command)?;
+        let handle = PTYHandle::spawn(task_id, command, &options)?;
 
         // Store handle
         {
@@ -54,13 +77,75 @@ impl Executor {
         // Send started event
         let _ = self.event_tx.send(TaskEvent::Started {
             task_id: task_id.to_string(),
+            pid: handle.pid(),
         });
 
+        // Enforce timeout: SIGTERM the process group, then SIGKILL after the grace period
+        let timed_out = Arc::new(AtomicBool::new(false));
+        if let Some(timeout) = options.timeout {
+            let grace = options.kill_grace.unwrap_or(DEFAULT_KILL_GRACE);
+            let watch_handle = handle.clone();
+            let timed_out = timed_out.clone();
+            let task_id_owned = task_id.to_string();
+
+            tokio::spawn(async move {
+                tokio::time::sleep(timeout).await;
+                if !matches!(watch_handle.try_wait(), Ok(None)) {
+                    return; // Already exited
+                }
+
+                log::warn!("Task {} timed out after {:?}", task_id_owned, timeout);
+                timed_out.store(true, Ordering::SeqCst);
+                terminate_group(&watch_handle, grace).await;
+            });
+        }
+
+        // Probe services until ready; terminate them if the probe times out
+        let not_ready = Arc::new(AtomicBool::new(false));
+        let output_seen = Arc::new(AtomicBool::new(false));
+        if let Some(probe) = options.ready.clone() {
+            let grace = options.kill_grace.unwrap_or(DEFAULT_KILL_GRACE);
+            let probe_handle = handle.clone();
+            let not_ready = not_ready.clone();
+            let output_seen = output_seen.clone();
+            let event_tx = self.event_tx.clone();
+            let task_id_owned = task_id.to_string();
+
+            tokio::spawn(async move {
+                let started = Instant::now();
+                loop {
+                    if !matches!(probe_handle.try_wait(), Ok(None)) {
+                        return; // Exited before becoming ready
+                    }
+
+                    let output_ok = !probe.watches_output() || output_seen.load(Ordering::SeqCst);
+                    if output_ok && probe.check().await {
+                        log::info!("Service {} is ready", task_id_owned);
+                        let _ = event_tx.send(TaskEvent::Ready {
+                            task_id: task_id_owned,
+                        });
+                        return;
+                    }
+
+                    if probe.timeout.is_some_and(|t| started.elapsed() >= t) {
+                        log::warn!("Service {} not ready after {:?}", task_id_owned, probe.timeout);
+                        not_ready.store(true, Ordering::SeqCst);
+                        terminate_group(&probe_handle, grace).await;
+                        return;
+                    }
+                    tokio::time::sleep(probe.interval).await;
+                }
+            });
+        }
+
         // Spawn reader task — uses spawn_blocking for the actual I/O
         let task_id_owned = task_id.to_string();
         let event_tx = self.event_tx.clone();
         let handles_ref = self.handles.clone();
         let reader_handle = handle.clone();
+        let timeout = options.timeout;
+        let ready_timeout = options.ready.as_ref().and_then(|p| p.timeout);
+        let output_probe = options.ready.filter(|p| p.watches_output());
 
         tokio::spawn(async move {
             loop {
@@ -75,6 +160,9 @@ impl Executor {
 
                 match line_result {
                     Ok(Ok(Some(line))) => {
+                        if output_probe.as_ref().is_some_and(|p| p.matches_output(&line)) {
+                            output_seen.store(true, Ordering::SeqCst);
+                        }
                         if !line.is_empty() {
                             let _ = event_tx.send(TaskEvent::Output {
                                 task_id: task_id_owned.clone(),
@@ -84,14 +172,23 @@ impl Executor {
                     }
                     Ok(Ok(None)) => {
                         // EOF — process ended, get exit code
-                        let exit_code = reader_handle
-                            .try_wait()
-                            .ok()
-                            .flatten()
-                            .map(|r| r.code)
-                            .unwrap_or(0);
-
-                        if exit_code == 0 {
+                        let exit_code = wait_for_exit(&reader_handle).await;
+
+                        if timed_out.load(Ordering::SeqCst) {
+                            let _ = event_tx.send(TaskEvent::TimedOut {
+                                task_id: task_id_owned.clone(),
+                                timeout: timeout.unwrap_or_default(),
+                            });
+                        } else if not_ready.load(Ordering::SeqCst) {
+                            let _ = event_tx.send(TaskEvent::Failed {
+                                task_id: task_id_owned.clone(),
+                                error: format!(
+                                    "Not ready within {}",
+                                    HumanDuration::from(ready_timeout.unwrap_or_default())
+                                ),
+                                exit_code: None,
+                            });
+                        } else if exit_code == 0 {
                             log::info!("Task {} completed (exit: {})", task_id_owned, exit_code);
                             let _ = event_tx.send(TaskEvent::Completed {
                                 task_id: task_id_owned.clone(),
@@ -102,6 +199,7 @@ impl Executor {
                             let _ = event_tx.send(TaskEvent::Failed {
                                 task_id: task_id_owned.clone(),
                                 error: format!("Process exited with code {}", exit_code),
+                                exit_code: Some(exit_code),
                             });
                         }
                         break;
@@ -111,6 +209,7 @@ impl Executor {
                         let _ = event_tx.send(TaskEvent::Failed {
                             task_id: task_id_owned.clone(),
                             error: e.to_string(),
+                            exit_code: None,
                         });
                         break;
                     }
@@ -120,6 +219,7 @@ impl Executor {
                         let _ = event_tx.send(TaskEvent::Failed {
                             task_id: task_id_owned.clone(),
                             error: format!("Internal error: {}", e),
+                            exit_code: None,
                         });
                         break;
                     }
@@ -134,13 +234,18 @@ impl Executor {
         Ok(())
     }
 
-    /// Stop a task (sends kill signal)
+    /// Stop a task and the processes it started: SIGTERM to its process
+    /// group, then SIGKILL once its `kill_grace` has passed
+    ///
+    /// Returns right away; the task reports its exit as usual.
     pub fn stop_task(&self, task_id: &str) -> Result<()> {
         let handles = self.handles.lock().unwrap();
 
         if let Some(handle) = handles.get(task_id) {
-            handle.kill()?;
-            log::info!("Stopped task: {}", task_id);
+            let handle = handle.clone();
+            let grace = handle.kill_grace.unwrap_or(DEFAULT_KILL_GRACE);
+            log::info!("Stopping task: {}", task_id);
+            tokio::spawn(async move { terminate_group(&handle, grace).await });
         }
 
         Ok(())
@@ -180,9 +285,83 @@ impl Executor {
     pub fn stop_all(&self) {
         let handles = self.handles.lock().unwrap();
         for (task_id, handle) in handles.iter() {
+            // No time for a grace period: warn the group, then kill the child
+            if let Err(e) = handle.signal_group(Signal::Term) {
+                log::warn!("Failed to terminate task {}: {}", task_id, e);
+            }
             if let Err(e) = handle.kill() {
                 log::warn!("Failed to kill task {}: {}", task_id, e);
             }
         }
     }
 }
+
+/// SIGTERM a task's process group, then SIGKILL it if still alive after `grace`
+async fn terminate_group(handle: &PTYHandle, grace: Duration) {
+    if let Err(e) = handle.signal_group(Signal::Term) {
+        log::warn!("Failed to terminate task {}: {}", handle.id, e);
+    }
+
+    tokio::time::sleep(grace).await;
+    if matches!(handle.try_wait(), Ok(None)) {
+        if let Err(e) = handle.signal_group(Signal::Kill) {
+            log::warn!("Failed to kill task {}: {}", handle.id, e);
+        }
+    }
+}
+
+/// Exit code of a task whose output just hit EOF
+///
+/// The PTY can close slightly before the child is reaped, so poll briefly
+/// instead of mistaking a still-exiting process for a success.
+async fn wait_for_exit(handle: &PTYHandle) -> i32 {
+    poll_exit_code(&handle.id, EXIT_WAIT, || handle.try_wait()).await
+}
+
+/// Poll `try_wait` for an exit code; a process that is still running after
+/// `limit`, or whose status cannot be read, counts as failed (-1)
+async fn poll_exit_code(
+    task_id: &str,
+    limit: Duration,
+    mut try_wait: impl FnMut() -> Result<Option<ExitResult>>,
+) -> i32 {
+    let started = Instant::now();
+    loop {
+        match try_wait() {
+            Ok(Some(result)) => return result.code,
+            Ok(None) if started.elapsed() < limit => tokio::time::sleep(Duration::from_millis(10)).await,
+            Ok(None) => {
+                log::warn!("Task {} closed its output but did not exit within {:?}", task_id, limit);
+                return -1;
+            }
+            Err(e) => {
+                log::warn!("Failed to get exit status of task {}: {}", task_id, e);
+                return -1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_exit_code_after_output_closes() {
+        // Reaped shortly after EOF
+        let mut polls = 0;
+        let code = poll_exit_code("build", Duration::from_secs(2), || {
+            polls += 1;
+            Ok((polls > 3).then_some(ExitResult { code: 2 }))
+        })
+        .await;
+        assert_eq!(code, 2);
+
+        // Never exits: a failure, not a success
+        let code = poll_exit_code("hung", Duration::from_millis(50), || Ok(None)).await;
+        assert_eq!(code, -1);
+
+        let code = poll_exit_code("gone", Duration::from_secs(2), || anyhow::bail!("no child")).await;
+        assert_eq!(code, -1);
+    }
+}
@@ -0,0 +1,529 @@
+//! Graph file formats - YAML, TOML and JSON, detected by file extension
+//!
+//! All formats deserialize into the same `Graph`, and parse errors are
+//! reported as `path:line:column: message` whatever the format.
+
+use anyhow::Result;
+use regex::Regex;
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Location suffix serde_yaml and serde_json append to error messages
+static LOCATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r",? at line \d+ column \d+$").unwrap());
+
+/// Graph file extensions tried when looking for a graph, in priority order
+pub const GRAPH_EXTENSIONS: [&str; 4] = ["yml", "yaml", "toml", "json"];
+
+/// Serialization format of a graph file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl GraphFormat {
+    /// Detect the format from a file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("yml") | Some("yaml") => Ok(Self::Yaml),
+            Some("toml") => Ok(Self::Toml),
+            Some("json") => Ok(Self::Json),
+            _ => anyhow::bail!(
+                "Unknown graph format for {} (expected .yml, .yaml, .toml or .json)",
+                path.display()
+            ),
+        }
+    }
+
+    /// Parse content, reporting errors as `path:line:column: message`
+    pub fn parse<T: DeserializeOwned>(&self, content: &str, path: &Path) -> Result<T> {
+        // serde_yaml and serde_json append " at line X column Y" to messages
+        let strip_location = |msg: String| -> String { LOCATION.replace(&msg, "").into_owned() };
+
+        let (line, column, message) = match self {
+            Self::Yaml => match serde_yaml::from_str(content) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let (line, column) = e
+                        .location()
+                        .map(|l| (l.line(), l.column()))
+                        .unwrap_or((1, 1));
+                    (line, column, strip_location(e.to_string()))
+                }
+            },
+            Self::Json => {
+                let mut de = serde_json::Deserializer::from_str(content);
+                match T::deserialize(AnyScalars(&mut de)).and_then(|value| de.end().map(|_| value)) {
+                    Ok(value) => return Ok(value),
+                    Err(e) => (e.line(), e.column(), strip_location(e.to_string())),
+                }
+            }
+            Self::Toml => match toml::from_str(content) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let (line, column) = e
+                        .span()
+                        .map(|span| line_column(content, span.start))
+                        .unwrap_or((1, 1));
+                    (line, column, e.message().to_string())
+                }
+            },
+        };
+
+        anyhow::bail!("{}:{}:{}: {}", path.display(), line, column, message)
+    }
+
+    /// Serialize a value, leaving out empty (null) fields
+    pub fn render<T: Serialize>(&self, value: &T) -> Result<String> {
+        let mut value = serde_json::to_value(value)?;
+        strip_nulls(&mut value);
+        Ok(match self {
+            Self::Yaml => serde_yaml::to_string(&value)?,
+            Self::Toml => toml::to_string_pretty(&value)?,
+            Self::Json => {
+                let mut out = serde_json::to_string_pretty(&value)?;
+                out.push('\n');
+                out
+            }
+        })
+    }
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Yaml => write!(f, "YAML"),
+            Self::Toml => write!(f, "TOML"),
+            Self::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map(|l| l.chars().count()).unwrap_or(0) + 1;
+    (line, column)
+}
+
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Accepts a string, number or bool and keeps it as text
+///
+/// YAML hands scalars over as written (`3.10` stays `3.10`); TOML and
+/// JSON numbers are formatted, so quote values where the text matters.
+struct ScalarString;
+
+impl<'de> Visitor<'de> for ScalarString {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string, number or boolean")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<String, E> {
+        Ok(v)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+}
+
+/// Newtype so collections of scalars can use `ScalarString`
+///
+/// Asks for a string, so YAML keeps `3.10` as written; JSON graphs are read
+/// through `AnyScalars`, which answers with numbers and booleans as they are.
+struct Scalar(String);
+
+impl<'de> serde::Deserialize<'de> for Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_string(ScalarString).map(Scalar)
+    }
+}
+
+/// JSON deserializer that serves string requests with `deserialize_any`
+///
+/// serde_json only hands out numbers and booleans through `deserialize_any`,
+/// so `Scalar` fields would reject them otherwise. Plain string fields still
+/// reject non-strings, their visitors do. Nested values are wrapped too.
+struct AnyScalars<D>(D);
+
+macro_rules! forward_wrapped {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+                self.0.$method($($arg,)* Wrapped(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for AnyScalars<D> {
+    type Error = D::Error;
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_any(Wrapped(visitor))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_any(Wrapped(visitor))
+    }
+
+    forward_wrapped! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+}
+
+/// Visitor that wraps the nested deserializers it is handed in `AnyScalars`
+struct Wrapped<V>(V);
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Wrapped<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.0.visit_some(AnyScalars(deserializer))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.0.visit_newtype_struct(AnyScalars(deserializer))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.0.visit_seq(AnyScalars(seq))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.0.visit_map(AnyScalars(map))
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.0.visit_enum(AnyScalars(data))
+    }
+}
+
+impl<'de, S: de::DeserializeSeed<'de>> de::DeserializeSeed<'de> for AnyScalars<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.0.deserialize(AnyScalars(deserializer))
+    }
+}
+
+impl<'de, A: de::SeqAccess<'de>> de::SeqAccess<'de> for AnyScalars<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error> {
+        self.0.next_element_seed(AnyScalars(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: de::MapAccess<'de>> de::MapAccess<'de> for AnyScalars<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+        self.0.next_key_seed(AnyScalars(seed))
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        self.0.next_value_seed(AnyScalars(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: de::EnumAccess<'de>> de::EnumAccess<'de> for AnyScalars<A> {
+    type Error = A::Error;
+    type Variant = AnyScalars<A::Variant>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), A::Error> {
+        let (value, variant) = self.0.variant_seed(AnyScalars(seed))?;
+        Ok((value, AnyScalars(variant)))
+    }
+}
+
+impl<'de, A: de::VariantAccess<'de>> de::VariantAccess<'de> for AnyScalars<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.0.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        self.0.newtype_variant_seed(AnyScalars(seed))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        self.0.tuple_variant(len, Wrapped(visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.0.struct_variant(fields, Wrapped(visitor))
+    }
+}
+
+/// Optional scalar (e.g. a port) that may be written as a number or a string
+pub fn deserialize_scalar<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let value: Option<Scalar> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(value.map(|v| v.0))
+}
+
+/// `env:` map whose values may be written as numbers or booleans in any format
+pub fn deserialize_env<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, String>>, D::Error> {
+    let map: Option<HashMap<String, Scalar>> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(map.map(|m| m.into_iter().map(|(k, v)| (k, v.0)).collect()))
+}
+
+/// `matrix:` map whose values may be written as numbers or booleans in any format
+pub fn deserialize_matrix<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<BTreeMap<String, Vec<String>>>, D::Error> {
+    let map: Option<BTreeMap<String, Vec<Scalar>>> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(map.map(|m| {
+        m.into_iter()
+            .map(|(k, values)| (k, values.into_iter().map(|v| v.0).collect()))
+            .collect()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Graph;
+
+    const YAML: &str = r#"
+metadata:
+  project: demo
+tasks:
+  build:
+    description: Build
+    command: cargo build
+    env: { PORT: 3000, DEBUG: true }
+  test:
+    description: Test
+    command: cargo test
+    depends_on: [build]
+"#;
+
+    const TOML: &str = r#"
+[metadata]
+project = "demo"
+
+[tasks.build]
+description = "Build"
+command = "cargo build"
+env = { PORT = 3000, DEBUG = true }
+
+[tasks.test]
+description = "Test"
+command = "cargo test"
+depends_on = ["build"]
+"#;
+
+    const JSON: &str = r#"{
+  "metadata": { "project": "demo" },
+  "tasks": {
+    "build": { "description": "Build", "command": "cargo build", "env": { "PORT": 3000, "DEBUG": true } },
+    "test": { "description": "Test", "command": "cargo test", "depends_on": ["build"] }
+  }
+}"#;
+
+    #[test]
+    fn test_formats_parse_identically() {
+        let path = Path::new("graph");
+        let yaml: Graph = GraphFormat::Yaml.parse(YAML, path).unwrap();
+        let toml: Graph = GraphFormat::Toml.parse(TOML, path).unwrap();
+        let json: Graph = GraphFormat::Json.parse(JSON, path).unwrap();
+
+        for graph in [&yaml, &toml, &json] {
+            assert_eq!(graph.tasks.len(), 2);
+            assert_eq!(graph.tasks["test"].depends_on, Some(vec!["build".to_string()]));
+            let env = graph.tasks["build"].env.as_ref().unwrap();
+            assert_eq!(env["PORT"], "3000");
+            assert_eq!(env["DEBUG"], "true");
+        }
+    }
+
+    #[test]
+    fn test_errors_point_at_line_and_column() {
+        let path = Path::new("graph.x");
+        let yaml_err = GraphFormat::Yaml
+            .parse::<Graph>("tasks:\n  build:\n    description: [\n", path)
+            .unwrap_err()
+            .to_string();
+        assert!(yaml_err.starts_with("graph.x:"), "{}", yaml_err);
+
+        let toml_err = GraphFormat::Toml
+            .parse::<Graph>("[tasks.build]\ndescription = \"Build\"\ncommand = \n", path)
+            .unwrap_err()
+            .to_string();
+        assert!(toml_err.starts_with("graph.x:3:"), "{}", toml_err);
+
+        let json_err = GraphFormat::Json
+            .parse::<Graph>("{\n  \"tasks\": {\n    \"build\": 1\n  }\n}", path)
+            .unwrap_err()
+            .to_string();
+        assert!(json_err.starts_with("graph.x:3:"), "{}", json_err);
+        assert!(!json_err.contains("at line"), "{}", json_err);
+    }
+
+    #[test]
+    fn test_json_scalars() {
+        let path = Path::new("graph.json");
+        let graph: Graph = GraphFormat::Json
+            .parse(
+                r#"{ "tasks": { "train": {
+                    "description": "Train", "command": "python train.py",
+                    "matrix": { "lr": [0.001, 1], "fp16": [true] },
+                    "kind": "service", "ready_when": { "tcp": 8080 } } } }"#,
+                path,
+            )
+            .unwrap();
+        let task = &graph.tasks["train"];
+        assert_eq!(task.matrix.as_ref().unwrap()["lr"], vec!["0.001", "1"]);
+        assert_eq!(task.matrix.as_ref().unwrap()["fp16"], vec!["true"]);
+        assert_eq!(task.ready_when.as_ref().unwrap().tcp.as_deref(), Some("8080"));
+
+        // Plain string fields still want strings, and trailing content is an error
+        let err = GraphFormat::Json
+            .parse::<Graph>(r#"{ "tasks": { "build": { "description": 3 } } }"#, path)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("expected a string"), "{}", err);
+        assert!(GraphFormat::Json.parse::<Graph>("{} {}", path).is_err());
+    }
+
+    #[test]
+    fn test_render_roundtrip() {
+        let graph: Graph = GraphFormat::Yaml.parse(YAML, Path::new("graph.yml")).unwrap();
+        for format in [GraphFormat::Yaml, GraphFormat::Toml, GraphFormat::Json] {
+            let out = format.render(&graph).unwrap();
+            assert!(!out.contains("null"), "{}", out);
+            let back: Graph = format.parse(&out, Path::new("out")).unwrap();
+            assert_eq!(back.tasks["test"].depends_on, graph.tasks["test"].depends_on);
+            assert_eq!(back.tasks["build"].env, graph.tasks["build"].env);
+        }
+    }
+}
@@ -1,10 +1,22 @@
 //! Graph parser - parses .gid/graph.yml and builds task DAG
 
+use super::duration::HumanDuration;
+use super::env::load_env_file;
+use super::format::{self, GRAPH_EXTENSIONS};
+use super::include::{self, Include};
+use super::interpolate;
+use super::matrix;
+use super::probe::{ReadinessProbe, ReadyWhen};
+use super::pty::{Shell, SpawnOptions};
+use super::policy::SchedulingConfig;
+use super::restart::RestartMode;
+use super::select::TaskSelection;
+use super::validation::{self, ValidationReport};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Task status enum — replaces raw status strings
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -16,6 +28,21 @@ pub enum GraphTaskStatus {
     Done,
     Failed,
     Planned,
+    /// Not run because its dependencies did not meet its `on` condition
+    Skipped,
+    /// Not run because its inputs and outputs are unchanged since the last run
+    Cached,
+    /// Service that passed its readiness probe and is still running
+    Ready,
+    /// Gate waiting for someone to approve or reject it
+    AwaitingApproval,
+}
+
+impl GraphTaskStatus {
+    /// Started (or held, for gates) but not finished
+    pub fn is_unfinished(&self) -> bool {
+        matches!(self, Self::InProgress | Self::Ready | Self::AwaitingApproval)
+    }
 }
 
 impl Default for GraphTaskStatus {
@@ -32,14 +59,66 @@ impl fmt::Display for GraphTaskStatus {
             Self::Done => write!(f, "done"),
             Self::Failed => write!(f, "failed"),
             Self::Planned => write!(f, "planned"),
+            Self::Skipped => write!(f, "skipped"),
+            Self::Cached => write!(f, "cached"),
+            Self::Ready => write!(f, "ready"),
+            Self::AwaitingApproval => write!(f, "awaiting-approval"),
         }
     }
 }
 
+/// When a task runs, based on how its dependencies finished (`on:` field)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunCondition {
+    /// All dependencies succeeded (default)
+    #[default]
+    Success,
+    /// At least one dependency failed or was skipped
+    Failure,
+    /// All dependencies finished, whatever the outcome
+    Always,
+}
+
+/// What kind of process a task runs (`kind:` field)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskKind {
+    /// Runs to completion (default)
+    #[default]
+    Task,
+    /// Keeps running; dependents start once it is ready (see `ready_when`)
+    Service,
+}
+
+/// How a dependency finished, from its dependents' point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DepState {
+    /// Still pending or running
+    Waiting,
+    /// Done, a ready service, or failed with `allow_failure`
+    Succeeded,
+    /// Failed or skipped
+    NotSucceeded,
+}
+
 /// Task graph representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Graph {
+    /// Other graph files merged into this one (resolved at load time)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<Include>,
+    /// Values for `${vars.name}` in task commands (substituted at load time)
+    #[serde(
+        default,
+        deserialize_with = "format::deserialize_env",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vars: Option<HashMap<String, String>>,
     pub metadata: Option<Metadata>,
+    /// Scheduling policy and parallelism limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduling: Option<SchedulingConfig>,
     #[serde(default)]
     pub nodes: HashMap<String, Node>,
     #[serde(default)]
@@ -65,10 +144,16 @@ pub struct Node {
     pub path: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Task {
+    /// Free-form type used to pick output parsers; `gate` makes the task a
+    /// manual approval step with no command
     #[serde(rename = "type", default)]
     pub task_type: String,
+    /// `task` (default) or `service` for processes that keep running
+    pub kind: Option<TaskKind>,
+    /// Readiness probe of a service: tcp, output, http and/or file
+    pub ready_when: Option<ReadyWhen>,
     pub description: String,
     pub command: Option<String>,
     #[serde(default)]
@@ -79,51 +164,298 @@ pub struct Task {
     pub estimated_hours: Option<u32>,
     pub tags: Option<Vec<String>>,
     pub semantic_commands: Option<HashMap<String, String>>,
+    /// Working directory for the command
+    pub cwd: Option<String>,
+    /// Extra environment variables (override `env_file`)
+    #[serde(default, deserialize_with = "format::deserialize_env")]
+    pub env: Option<HashMap<String, String>>,
+    /// `.env` style file loaded into the environment (relative to `cwd`)
+    pub env_file: Option<String>,
+    /// Shell to run the command with: sh (default), bash, zsh, login, or a path
+    pub shell: Option<String>,
+    /// Maximum run time (e.g. `30s`, `10m`); the task is terminated when exceeded
+    pub timeout: Option<HumanDuration>,
+    /// Time between SIGTERM and SIGKILL on timeout (default 5s)
+    pub kill_grace: Option<HumanDuration>,
+    /// Number of times to retry a failed run
+    pub retries: Option<u32>,
+    /// Delay before the first retry (default 1s)
+    pub retry_delay: Option<HumanDuration>,
+    /// Delay multiplier per further retry (default 2, use 1 for a fixed delay)
+    pub retry_backoff: Option<f64>,
+    /// Only retry when the process exits with one of these codes
+    pub retry_on_exit_codes: Option<Vec<i32>>,
+    /// Restart the process when it exits: always, on-failure or never (default)
+    pub restart: Option<RestartMode>,
+    /// Give up after this many restarts (default: no limit)
+    pub max_restarts: Option<u32>,
+    /// Delay before the first restart (default 1s)
+    pub restart_delay: Option<HumanDuration>,
+    /// Delay multiplier per further restart (default 2)
+    pub restart_backoff: Option<f64>,
+    /// Treat a failure of this task as success for its dependents
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_failure: bool,
+    /// Run when dependencies succeed (default), fail, or always
+    #[serde(rename = "on")]
+    pub run_on: Option<RunCondition>,
+    /// Files the task reads (globs relative to its working directory);
+    /// unchanged inputs and outputs let the task be skipped as cached
+    pub inputs: Option<Vec<String>>,
+    /// Files the task produces (globs relative to its working directory)
+    pub outputs: Option<Vec<String>>,
+    /// Files that re-run the task when they change under `run --watch`
+    /// (globs relative to its working directory)
+    pub watch: Option<Vec<String>>,
+    /// Values for dependents, as `key: regex` over the output (first group,
+    /// else the whole match); also read from `$GIDTERM_OUTPUT` as `key=value`
+    pub outputs_capture: Option<HashMap<String, String>>,
+    /// Run once per combination of values, e.g. `lr: [1e-3, 1e-4]`;
+    /// use `${matrix.lr}` in the command (expanded at load time)
+    #[serde(default, deserialize_with = "format::deserialize_matrix")]
+    pub matrix: Option<BTreeMap<String, Vec<String>>>,
+    /// Matrix task this task was expanded from
+    #[serde(skip)]
+    pub matrix_group: Option<String>,
+    /// Matrix values of this variant
+    #[serde(skip)]
+    pub matrix_values: Option<BTreeMap<String, String>>,
+    /// File the task was defined in, if it came from an `include:`
+    #[serde(skip)]
+    pub included_from: Option<PathBuf>,
+    /// Project root the task runs in (set in workspace mode, not read from YAML)
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+}
+
+impl Task {
+    /// True for `kind: service` tasks
+    pub fn is_service(&self) -> bool {
+        self.kind == Some(TaskKind::Service)
+    }
+
+    /// True for `type: gate` tasks, which wait for approval instead of running
+    pub fn is_gate(&self) -> bool {
+        self.task_type == "gate"
+    }
+
+    /// Apply `f` to the description and every field that may hold `${...}`
+    /// variables
+    pub fn rewrite_fields(&mut self, f: impl Fn(&str) -> String) {
+        let _ = self.try_rewrite_fields(|text| Ok(f(text)));
+    }
+
+    /// Like [`Task::rewrite_fields`], stopping at the first error (fields
+    /// rewritten before it keep their new value)
+    pub fn try_rewrite_fields(&mut self, mut f: impl FnMut(&str) -> Result<String>) -> Result<()> {
+        self.description = f(&self.description)?;
+        for field in [&mut self.command, &mut self.cwd, &mut self.env_file].into_iter().flatten() {
+            *field = f(field)?;
+        }
+        for globs in [&mut self.inputs, &mut self.outputs, &mut self.watch]
+            .into_iter()
+            .flatten()
+        {
+            for pattern in globs.iter_mut() {
+                *pattern = f(pattern)?;
+            }
+        }
+        for field in self.ready_when.iter_mut().flat_map(|r| r.fields_mut()) {
+            *field = f(field)?;
+        }
+        for map in [&mut self.env, &mut self.semantic_commands].into_iter().flatten() {
+            for value in map.values_mut() {
+                *value = f(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The description and every other field that may hold `${...}`
+    /// variables, in the order [`Task::rewrite_fields`] visits them
+    pub fn variable_fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        let _ = self.clone().try_rewrite_fields(|text| {
+            fields.push(text.to_string());
+            Ok(text.to_string())
+        });
+        fields
+    }
+
+    /// Build PTY spawn options from the task's cwd/env/env_file/shell fields
+    ///
+    /// A relative `cwd` is resolved against the project root when known.
+    pub fn spawn_options(&self) -> Result<SpawnOptions> {
+        let cwd = match (&self.root, &self.cwd) {
+            (Some(root), Some(cwd)) => Some(root.join(cwd)),
+            (Some(root), None) => Some(root.clone()),
+            (None, cwd) => cwd.as_ref().map(PathBuf::from),
+        };
+
+        let mut env = HashMap::new();
+        if let Some(env_file) = &self.env_file {
+            let path = match &cwd {
+                Some(dir) => dir.join(env_file),
+                None => PathBuf::from(env_file),
+            };
+            env.extend(load_env_file(&path)?);
+        }
+        if let Some(task_env) = &self.env {
+            env.extend(task_env.clone());
+        }
+
+        // Services without a probe are ready as soon as they start
+        let ready = if self.is_service() {
+            let ready_when = self.ready_when.clone().unwrap_or_default();
+            Some(ReadinessProbe::new(&ready_when, cwd.as_deref())?)
+        } else {
+            None
+        };
+
+        Ok(SpawnOptions {
+            cwd,
+            env,
+            shell: self.shell.as_deref().map(Shell::from_name).unwrap_or_default(),
+            timeout: self.timeout.map(|t| t.as_duration()),
+            kill_grace: self.kill_grace.map(|t| t.as_duration()),
+            ready,
+        })
+    }
 }
 
 impl Graph {
-    /// Load graph from YAML file
+    /// Load graph from YAML file and validate it
+    ///
+    /// Validation errors (cycles, unknown dependencies) fail the load;
+    /// warnings are logged.
     pub fn from_file(path: &Path) -> Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        let graph: Graph = serde_yaml::from_str(&content)?;
+        let graph = Self::from_file_unchecked(path)?;
+        graph.ensure_valid(path)?;
+        Ok(graph)
+    }
+
+    /// Load graph from a YAML, TOML or JSON file (and its includes) without validating it
+    pub fn from_file_unchecked(path: &Path) -> Result<Self> {
+        let mut graph = include::load(path)?;
+        graph.expand_matrices()?;
+        graph.substitute_vars();
+        graph.reset_interrupted();
         Ok(graph)
     }
 
     /// Load from gid project directory
     pub fn from_gid_project(project_dir: &Path) -> Result<Self> {
-        let gid_path = project_dir.join(".gid/graph.yml");
-        if !gid_path.exists() {
+        let Some(gid_path) = Self::find_file(&project_dir.join(".gid"), "graph") else {
             anyhow::bail!("No .gid/graph.yml found in {:?}", project_dir);
-        }
+        };
         Self::from_file(&gid_path)
     }
 
     /// Auto-detect and load graph from current directory
     /// Priority:
-    /// 1. .gid/graph.yml (gid project)
-    /// 2. gidterm.yml (standalone config)
+    /// 1. .gid/graph.{yml,yaml,toml,json} (gid project)
+    /// 2. gidterm.{yml,yaml,toml,json} (standalone config)
     /// 3. Return error if none found
     pub fn auto_load() -> Result<Self> {
-        // Try .gid/graph.yml first
-        let gid_path = Path::new(".gid/graph.yml");
-        if gid_path.exists() {
-            return Self::from_file(gid_path);
+        Self::from_file(&Self::detect_path()?)
+    }
+
+    /// Find the graph file in the current directory (see `auto_load`)
+    pub fn detect_path() -> Result<PathBuf> {
+        // Try .gid/graph.* first, then fall back to gidterm.*
+        Self::find_file(Path::new(".gid"), "graph")
+            .or_else(|| Self::find_file(Path::new(""), "gidterm"))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No graph file found. Expected .gid/graph.yml or gidterm.yml \
+                     (or .yaml/.toml/.json) in current directory."
+                )
+            })
+    }
+
+    /// Find `<dir>/<stem>.<ext>` for the first supported extension that exists
+    pub fn find_file(dir: &Path, stem: &str) -> Option<PathBuf> {
+        GRAPH_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+            .find(|path| path.exists())
+    }
+
+    /// Check the graph for cycles, dangling dependencies and ID collisions
+    pub fn validate(&self) -> ValidationReport {
+        validation::validate(self)
+    }
+
+    /// Validate and turn errors into a load failure, logging warnings
+    fn ensure_valid(&self, path: &Path) -> Result<()> {
+        let report = self.validate();
+        for warning in report.warnings() {
+            log::warn!("{}: {}", path.display(), warning);
         }
 
-        // Fall back to gidterm.yml
-        let standalone_path = Path::new("gidterm.yml");
-        if standalone_path.exists() {
-            return Self::from_file(standalone_path);
+        let errors = report.errors();
+        if !errors.is_empty() {
+            let details: Vec<String> = errors.iter().map(|e| format!("  - {}", e)).collect();
+            anyhow::bail!(
+                "Invalid graph {}:\n{}\nRun `gidterm validate -g {}` for details.",
+                path.display(),
+                details.join("\n"),
+                path.display()
+            );
         }
+        Ok(())
+    }
+
+    /// Make tasks a previous run left unfinished (`in-progress`, `ready` or
+    /// `awaiting-approval` in the file) pending again, so they run
+    pub fn reset_interrupted(&mut self) {
+        for task in self.tasks.values_mut() {
+            if task.status.is_unfinished() {
+                task.status = GraphTaskStatus::Pending;
+            }
+        }
+    }
+
+    /// Expand `matrix:` tasks into one task per combination of values
+    pub fn expand_matrices(&mut self) -> Result<()> {
+        matrix::expand(self)
+    }
+
+    /// Replace `${vars.name}` in task fields with the graph's `vars:` values
+    ///
+    /// Unknown names are left in place and reported by validation.
+    pub fn substitute_vars(&mut self) {
+        let Some(vars) = &self.vars else {
+            return;
+        };
+        for task in self.tasks.values_mut() {
+            task.rewrite_fields(|text| interpolate::substitute_vars(text, vars));
+        }
+    }
+
+    /// Copy of the graph with only the selected tasks and their dependencies
+    pub fn select(&self, selection: &TaskSelection) -> Result<Graph> {
+        selection.apply(self)
+    }
 
-        anyhow::bail!(
-            "No graph file found. Expected .gid/graph.yml or gidterm.yml in current directory."
-        )
+    /// Variant IDs of each matrix group, sorted
+    pub fn matrix_groups(&self) -> BTreeMap<String, Vec<String>> {
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (id, task) in &self.tasks {
+            if let Some(group) = &task.matrix_group {
+                groups.entry(group.clone()).or_default().push(id.clone());
+            }
+        }
+        for variants in groups.values_mut() {
+            variants.sort();
+        }
+        groups
     }
 
-    /// Get all tasks ready to run (dependencies met)
+    /// Get all tasks ready to run (dependencies met), sorted by ID
     pub fn get_ready_tasks(&self) -> Vec<String> {
-        self.tasks
+        let mut ready: Vec<String> = self
+            .tasks
             .iter()
             .filter_map(|(id, task)| {
                 if self.can_start(id) && task.status == GraphTaskStatus::Pending {
@@ -132,27 +464,66 @@ impl Graph {
                     None
                 }
             })
-            .collect()
+            .collect();
+        ready.sort();
+        ready
     }
 
-    /// Check if a task can start (all dependencies done)
+    /// Check if a task can start (dependencies satisfy its `on` condition)
     pub fn can_start(&self, task_id: &str) -> bool {
         let Some(task) = self.tasks.get(task_id) else {
             return false;
         };
 
-        // If no dependencies, can start
-        let Some(deps) = &task.depends_on else {
-            return true;
+        let states = self.dependency_states(task);
+        let settled = !states.contains(&DepState::Waiting);
+        let all_succeeded = states.iter().all(|s| *s == DepState::Succeeded);
+
+        match task.run_on.unwrap_or_default() {
+            RunCondition::Success => all_succeeded,
+            RunCondition::Failure => settled && !all_succeeded,
+            RunCondition::Always => settled,
+        }
+    }
+
+    /// Check if a pending task can never satisfy its `on` condition
+    ///
+    /// With `on: success` (the default) a task is skipped as soon as one of
+    /// its dependencies failed or was skipped; with `on: failure` it is
+    /// skipped once every dependency succeeded.
+    pub fn should_skip(&self, task_id: &str) -> bool {
+        let Some(task) = self.tasks.get(task_id) else {
+            return false;
         };
+        if task.status != GraphTaskStatus::Pending {
+            return false;
+        }
 
-        // All dependencies must be in "done" status
-        deps.iter().all(|dep_id| {
-            self.tasks
-                .get(dep_id)
-                .map(|dep_task| dep_task.status == GraphTaskStatus::Done)
-                .unwrap_or(false)
-        })
+        let states = self.dependency_states(task);
+        match task.run_on.unwrap_or_default() {
+            RunCondition::Success => states.contains(&DepState::NotSucceeded),
+            RunCondition::Failure => states.iter().all(|s| *s == DepState::Succeeded),
+            RunCondition::Always => false,
+        }
+    }
+
+    /// Outcome of each dependency of a task, as seen by its dependents
+    fn dependency_states(&self, task: &Task) -> Vec<DepState> {
+        task.depends_on
+            .iter()
+            .flatten()
+            .map(|dep_id| match self.tasks.get(dep_id) {
+                Some(dep) => match dep.status {
+                    GraphTaskStatus::Done | GraphTaskStatus::Cached | GraphTaskStatus::Ready => {
+                        DepState::Succeeded
+                    }
+                    GraphTaskStatus::Failed if dep.allow_failure => DepState::Succeeded,
+                    GraphTaskStatus::Failed | GraphTaskStatus::Skipped => DepState::NotSucceeded,
+                    _ => DepState::Waiting,
+                },
+                None => DepState::Waiting,
+            })
+            .collect()
     }
 
     /// Update task status
@@ -176,6 +547,19 @@ impl Graph {
     }
 }
 
+/// Split a reference to a task in another project: `backend:build` or
+/// `@backend/build` give `("backend", "build")`
+///
+/// Only meaningful for dependencies that are not local task IDs, since a
+/// task ID may itself contain `:`.
+pub fn split_project_ref(dep: &str) -> Option<(&str, &str)> {
+    let (project, task) = match dep.strip_prefix('@') {
+        Some(rest) => rest.rsplit_once('/')?,
+        None => dep.split_once(':')?,
+    };
+    (!project.is_empty() && !task.is_empty()).then_some((project, task))
+}
+
 #[cfg(test)]
 mod tests {
     
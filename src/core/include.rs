@@ -0,0 +1,300 @@
+//! Graph includes - compose a graph from several graph files
+//!
+//! ```yaml
+//! include:
+//!   - ../shared/lint.yml                          # tasks keep their IDs
+//!   - { path: ../shared/release.yml, namespace: release }  # tasks become release:<id>
+//! ```
+//!
+//! Paths are relative to the including file and may use any supported
+//! format. Included files may include other files. A dependency that names
+//! a task in the same included file follows that task's namespace; any
+//! other dependency is resolved against the whole composed graph.
+
+use super::format::GraphFormat;
+use super::interpolate;
+use super::{Graph, Node, Task};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// One `include:` entry: a path, optionally with a namespace prefix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Include {
+    Path(String),
+    Namespaced {
+        path: String,
+        namespace: Option<String>,
+    },
+}
+
+impl Include {
+    /// Path as written (relative to the including file)
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Path(path) => path,
+            Self::Namespaced { path, .. } => path,
+        }
+    }
+
+    /// Namespace prefix for the included IDs, if any
+    pub fn namespace(&self) -> Option<&str> {
+        match self {
+            Self::Path(_) => None,
+            Self::Namespaced { namespace, .. } => namespace.as_deref(),
+        }
+    }
+}
+
+/// Load a graph file and everything it includes
+pub fn load(path: &Path) -> Result<Graph> {
+    load_recursive(path, &mut Vec::new())
+}
+
+fn load_recursive(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Graph> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Failed to read graph {}: {}", path.display(), e))?;
+    if let Some(start) = stack.iter().position(|p| *p == canonical) {
+        let chain: Vec<String> = stack[start..]
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        anyhow::bail!("Include cycle: {}", chain.join(" -> "));
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let mut graph: Graph = GraphFormat::from_path(path)?.parse(&content, path)?;
+    graph.expand_matrices()?;
+
+    let includes = std::mem::take(&mut graph.include);
+    if includes.is_empty() {
+        return Ok(graph);
+    }
+
+    stack.push(canonical);
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    // Where each ID came from, for collision errors
+    let mut task_sources: HashMap<String, PathBuf> = graph
+        .tasks
+        .keys()
+        .map(|id| (id.clone(), path.to_path_buf()))
+        .collect();
+    let mut node_sources: HashMap<String, PathBuf> = graph
+        .nodes
+        .keys()
+        .map(|id| (id.clone(), path.to_path_buf()))
+        .collect();
+
+    for include in &includes {
+        let include_path = base.join(include.path());
+        let mut included = load_recursive(&include_path, stack)
+            .map_err(|e| anyhow::anyhow!("{} (included from {})", e, path.display()))?;
+        if let Some(namespace) = include.namespace() {
+            apply_namespace(&mut included, namespace);
+        }
+
+        // The including file's vars and scheduling win over the included file's
+        graph.scheduling = graph.scheduling.or(included.scheduling.take());
+        if let Some(vars) = included.vars.take() {
+            let merged = graph.vars.get_or_insert_with(HashMap::new);
+            for (name, value) in vars {
+                merged.entry(name).or_insert(value);
+            }
+        }
+
+        let mut ids: Vec<String> = included.tasks.keys().cloned().collect();
+        ids.sort();
+        for id in ids {
+            let mut task = included.tasks.remove(&id).unwrap();
+            let source = task.included_from.clone().unwrap_or_else(|| include_path.clone());
+            if let Some(existing) = task_sources.get(&id) {
+                anyhow::bail!(
+                    "Task '{}' is defined in both {} and {}",
+                    id,
+                    existing.display(),
+                    source.display()
+                );
+            }
+            task.included_from = Some(source.clone());
+            task_sources.insert(id.clone(), source);
+            graph.tasks.insert(id, task);
+        }
+
+        for (id, node) in included.nodes {
+            if let Some(existing) = node_sources.get(&id) {
+                anyhow::bail!(
+                    "Node '{}' is defined in both {} and {}",
+                    id,
+                    existing.display(),
+                    include_path.display()
+                );
+            }
+            node_sources.insert(id.clone(), include_path.clone());
+            graph.nodes.insert(id, node);
+        }
+    }
+
+    stack.pop();
+    Ok(graph)
+}
+
+/// Prefix task and node IDs with `namespace:`, along with references between them
+fn apply_namespace(graph: &mut Graph, namespace: &str) {
+    let prefix = |id: &str| format!("{}:{}", namespace, id);
+
+    let tasks: HashMap<String, Task> = std::mem::take(&mut graph.tasks);
+    let local_tasks: Vec<String> = tasks.keys().cloned().collect();
+    for (id, mut task) in tasks {
+        if let Some(deps) = &task.depends_on {
+            task.depends_on = Some(
+                deps.iter()
+                    .map(|dep| if local_tasks.contains(dep) { prefix(dep) } else { dep.clone() })
+                    .collect(),
+            );
+        }
+        if let Some(group) = &task.matrix_group {
+            task.matrix_group = Some(prefix(group));
+        }
+        task.rewrite_fields(|text| {
+            interpolate::rename_output_sources(text, |source| {
+                local_tasks.iter().any(|t| t == source).then(|| prefix(source))
+            })
+        });
+        graph.tasks.insert(prefix(&id), task);
+    }
+
+    let nodes: HashMap<String, Node> = std::mem::take(&mut graph.nodes);
+    let local_nodes: Vec<String> = nodes.keys().cloned().collect();
+    for (id, mut node) in nodes {
+        if let Some(deps) = &node.depends_on {
+            node.depends_on = Some(
+                deps.iter()
+                    .map(|dep| if local_nodes.contains(dep) { prefix(dep) } else { dep.clone() })
+                    .collect(),
+            );
+        }
+        graph.nodes.insert(prefix(&id), node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_include_with_namespace() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("shared")).unwrap();
+        fs::write(
+            dir.path().join("shared/checks.yml"),
+            r#"
+tasks:
+  lint: { description: Lint, command: cargo clippy, depends_on: [build] }
+  test: { description: Test, command: cargo test, depends_on: [lint] }
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("graph.yml"),
+            r#"
+include:
+  - { path: shared/checks.yml, namespace: checks }
+tasks:
+  build: { description: Build, command: cargo build }
+  release: { description: Release, command: make release, depends_on: ["checks:test"] }
+"#,
+        )
+        .unwrap();
+
+        let graph = load(&dir.path().join("graph.yml")).unwrap();
+        assert_eq!(graph.tasks.len(), 4);
+        // Local references follow the namespace, others resolve in the parent
+        assert_eq!(
+            graph.tasks["checks:test"].depends_on,
+            Some(vec!["checks:lint".to_string()])
+        );
+        assert_eq!(
+            graph.tasks["checks:lint"].depends_on,
+            Some(vec!["build".to_string()])
+        );
+        assert!(graph.tasks["checks:lint"].included_from.is_some());
+        assert!(graph.tasks["build"].included_from.is_none());
+        assert!(graph.validate().is_ok());
+    }
+
+    #[test]
+    fn test_namespace_rewrites_output_references() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("release.yml"),
+            r#"
+tasks:
+  build:
+    description: Build
+    command: ./build.sh
+    outputs_capture: { version: "v(\\S+)" }
+  deploy:
+    description: Deploy
+    command: "./deploy ${tasks.build.outputs.version} ${tasks.setup.outputs.region}"
+    depends_on: [build, setup]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("graph.yml"),
+            r#"
+include:
+  - { path: release.yml, namespace: rel }
+tasks:
+  setup:
+    description: Setup
+    command: ./setup.sh
+    outputs_capture: { region: "region=(\\S+)" }
+"#,
+        )
+        .unwrap();
+
+        let graph = load(&dir.path().join("graph.yml")).unwrap();
+        assert_eq!(
+            graph.tasks["rel:deploy"].command.as_deref(),
+            Some("./deploy ${tasks.rel:build.outputs.version} ${tasks.setup.outputs.region}")
+        );
+        let report = graph.validate();
+        assert!(report.is_ok(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_include_collision_names_both_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("lint.yml"),
+            "tasks:\n  lint: { description: Lint, command: cargo clippy }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("graph.yml"),
+            "include: [lint.yml]\ntasks:\n  lint: { description: Lint, command: eslint . }\n",
+        )
+        .unwrap();
+
+        let err = load(&dir.path().join("graph.yml")).unwrap_err().to_string();
+        assert!(err.contains("Task 'lint' is defined in both"), "{}", err);
+        assert!(err.contains("graph.yml") && err.contains("lint.yml"), "{}", err);
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("a.yml"), "include: [b.yml]\ntasks: {}\n").unwrap();
+        fs::write(dir.path().join("b.yml"), "include: [a.yml]\ntasks: {}\n").unwrap();
+
+        let err = load(&dir.path().join("a.yml")).unwrap_err().to_string();
+        assert!(err.contains("Include cycle"), "{}", err);
+    }
+}
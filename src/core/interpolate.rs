@@ -0,0 +1,263 @@
+//! Variable interpolation in task commands, paths and environment
+//!
+//! Recognized references:
+//! - `${vars.name}` - graph `vars:` section (substituted when the graph loads)
+//! - `${env.NAME}` - environment variable (task `env` first, then gidterm's own)
+//! - `${project.root}`, `${project.name}` - project the task belongs to
+//! - `${port}` - port allocated to the project
+//! - `${task.id}` - ID of the task
+//! - `${tasks.<id>.outputs.<key>}` - value captured from an upstream task
+//!
+//! Anything else (e.g. `${HOME}`) is left for the shell.
+
+use super::Task;
+use anyhow::Result;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+/// Namespaces gidterm interpolates itself
+const NAMESPACES: [&str; 7] = ["vars", "env", "project", "port", "task", "tasks", "matrix"];
+
+/// `${namespace}` or `${namespace.field}`
+static PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?:\.([^}\s]+))?\}").unwrap());
+
+/// A `${namespace.field}` reference found in a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub namespace: String,
+    pub field: Option<String>,
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "${{{}.{}}}", self.namespace, field),
+            None => write!(f, "${{{}}}", self.namespace),
+        }
+    }
+}
+
+/// Values available when a task starts
+#[derive(Debug, Clone, Default)]
+pub struct VarContext {
+    pub vars: HashMap<String, String>,
+    pub task_id: String,
+    pub project_name: Option<String>,
+    pub project_root: PathBuf,
+    pub port: Option<u16>,
+    /// Captured output values of finished tasks (task ID -> key -> value)
+    pub task_outputs: HashMap<String, HashMap<String, String>>,
+}
+
+impl VarContext {
+    /// Resolve one reference (`None` if it cannot be resolved)
+    fn resolve(&self, reference: &Reference, task_env: &HashMap<String, String>) -> Option<String> {
+        let field = reference.field.as_deref();
+        match (reference.namespace.as_str(), field) {
+            ("vars", Some(name)) => self.vars.get(name).cloned(),
+            ("env", Some(name)) => task_env
+                .get(name)
+                .cloned()
+                .or_else(|| std::env::var(name).ok()),
+            ("project", Some("root")) => Some(self.project_root.display().to_string()),
+            ("project", Some("name")) => self.project_name.clone(),
+            ("port", None) => self.port.map(|p| p.to_string()),
+            ("task", Some("id")) => Some(self.task_id.clone()),
+            ("tasks", Some(field)) => {
+                let (task_id, key) = output_reference(field)?;
+                self.task_outputs.get(task_id)?.get(key).cloned()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Split the field of `${tasks.<id>.outputs.<key>}` into task ID and key
+///
+/// Task IDs may contain dots (e.g. matrix values), so split at the last `.outputs.`.
+pub fn output_reference(field: &str) -> Option<(&str, &str)> {
+    field
+        .rsplit_once(".outputs.")
+        .filter(|(task_id, key)| !task_id.is_empty() && !key.is_empty())
+}
+
+fn reference(caps: &Captures) -> Option<Reference> {
+    let namespace = &caps[1];
+    if !NAMESPACES.contains(&namespace) {
+        return None; // Left for the shell
+    }
+    Some(Reference {
+        namespace: namespace.to_string(),
+        field: caps.get(2).map(|m| m.as_str().to_string()),
+    })
+}
+
+/// All gidterm references in a string
+pub fn references(text: &str) -> Vec<Reference> {
+    PATTERN
+        .captures_iter(text)
+        .filter_map(|caps| reference(&caps))
+        .collect()
+}
+
+/// Replace `${vars.name}` for the names that are defined, leaving the rest
+pub fn substitute_vars(text: &str, vars: &HashMap<String, String>) -> String {
+    PATTERN
+        .replace_all(text, |caps: &Captures| match reference(caps) {
+            Some(Reference { namespace, field: Some(name) }) if namespace == "vars" => vars
+                .get(&name)
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string()),
+            _ => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Rename the source task of `${tasks.<id>.outputs.<key>}` references;
+/// `rename` returns the new ID, or `None` to leave the reference alone
+pub fn rename_output_sources(text: &str, rename: impl Fn(&str) -> Option<String>) -> String {
+    PATTERN
+        .replace_all(text, |caps: &Captures| {
+            let renamed = match reference(caps) {
+                Some(Reference { namespace, field: Some(field) }) if namespace == "tasks" => {
+                    output_reference(&field)
+                        .and_then(|(source, key)| Some(format!("${{tasks.{}.outputs.{}}}", rename(source)?, key)))
+                }
+                _ => None,
+            };
+            renamed.unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+/// Resolve every reference in a string, failing on the first unresolved one
+pub fn interpolate(
+    text: &str,
+    ctx: &VarContext,
+    task_env: &HashMap<String, String>,
+) -> Result<String> {
+    let mut unresolved = Vec::new();
+    let out = PATTERN
+        .replace_all(text, |caps: &Captures| match reference(caps) {
+            Some(r) => ctx.resolve(&r, task_env).unwrap_or_else(|| {
+                unresolved.push(r.to_string());
+                caps[0].to_string()
+            }),
+            None => caps[0].to_string(),
+        })
+        .into_owned();
+
+    if !unresolved.is_empty() {
+        anyhow::bail!("Unresolved variable(s): {}", unresolved.join(", "));
+    }
+    Ok(out)
+}
+
+/// Resolve every field of a task that may hold variables (see
+/// [`Task::try_rewrite_fields`])
+pub fn resolve_task(task: &Task, ctx: &VarContext) -> Result<Task> {
+    let task_env = task.env.clone().unwrap_or_default();
+    let mut resolved = task.clone();
+
+    let env = resolved.env.take();
+    resolved.try_rewrite_fields(|text| interpolate(text, ctx, &task_env))?;
+    if let Some(env) = env {
+        let mut out = HashMap::new();
+        for (key, value) in env {
+            // Values may refer to the process environment, not to each other
+            let value = interpolate(&value, ctx, &HashMap::new())?;
+            out.insert(key, value);
+        }
+        resolved.env = Some(out);
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> VarContext {
+        VarContext {
+            vars: HashMap::from([("model".to_string(), "resnet50".to_string())]),
+            task_id: "train".to_string(),
+            project_name: Some("ml".to_string()),
+            project_root: PathBuf::from("/work/ml"),
+            port: Some(3001),
+            task_outputs: HashMap::from([(
+                "build[py=3.10]".to_string(),
+                HashMap::from([("tag".to_string(), "app:abc".to_string())]),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let env = HashMap::from([("DATA".to_string(), "/data".to_string())]);
+        let out = interpolate(
+            "python train.py --model ${vars.model} --data ${env.DATA} --out ${project.root}/runs/${task.id} --port ${port} --home ${HOME} --image ${tasks.build[py=3.10].outputs.tag}",
+            &ctx(),
+            &env,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "python train.py --model resnet50 --data /data --out /work/ml/runs/train --port 3001 --home ${HOME} --image app:abc"
+        );
+    }
+
+    #[test]
+    fn test_unresolved() {
+        let err = interpolate("echo ${vars.missing} ${task.name}", &ctx(), &HashMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("${vars.missing}") && err.contains("${task.name}"), "{}", err);
+    }
+
+    #[test]
+    fn test_resolve_task_fields() {
+        let task: Task = serde_yaml::from_str(
+            r#"
+description: "Serve ${vars.model} on ${port}"
+command: "serve --model ${vars.model} --name ${task.id}"
+kind: service
+ready_when: { tcp: "${port}" }
+env: { DATA: "${project.root}/data" }
+"#,
+        )
+        .unwrap();
+        let resolved = resolve_task(&task, &ctx()).unwrap();
+        assert_eq!(resolved.description, "Serve resnet50 on 3001");
+        assert_eq!(resolved.ready_when.unwrap().tcp.as_deref(), Some("3001"));
+        assert_eq!(resolved.env.unwrap()["DATA"], "/work/ml/data");
+        assert_eq!(resolved.command.as_deref(), Some("serve --model resnet50 --name train"));
+
+        // env values cannot refer to each other
+        let task: Task =
+            serde_yaml::from_str("{ description: X, env: { A: a, B: \"${env.A}\" } }").unwrap();
+        if std::env::var_os("A").is_none() {
+            assert!(resolve_task(&task, &ctx()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_substitute_vars_keeps_unknown() {
+        let vars = HashMap::from([("a".to_string(), "1".to_string())]);
+        assert_eq!(
+            substitute_vars("${vars.a} ${vars.b} ${env.X}", &vars),
+            "1 ${vars.b} ${env.X}"
+        );
+        assert_eq!(
+            references("${vars.b} ${port} ${PATH}"),
+            vec![
+                Reference { namespace: "vars".into(), field: Some("b".into()) },
+                Reference { namespace: "port".into(), field: None },
+            ]
+        );
+    }
+}
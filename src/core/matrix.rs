@@ -0,0 +1,231 @@
+//! Matrix expansion - turn one task with a `matrix:` into a task per combination
+//!
+//! ```yaml
+//! train:
+//!   command: "python train.py --lr ${matrix.lr} --py ${matrix.python}"
+//!   matrix:
+//!     lr: [1e-3, 1e-4]
+//!     python: [3.10, 3.11]
+//! ```
+//!
+//! expands to `train[lr=1e-3,python=3.10]`, `train[lr=1e-3,python=3.11]`, ...
+//! Tasks that depend on `train` depend on every variant.
+
+use super::{Graph, Task};
+use anyhow::Result;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// `${matrix.key}`, capturing the key
+static MATRIX_VAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$\{matrix\.([A-Za-z0-9_-]+)\}").unwrap());
+
+/// Expand every matrix task in the graph, in place
+pub fn expand(graph: &mut Graph) -> Result<()> {
+    let mut matrix_ids: Vec<String> = graph
+        .tasks
+        .iter()
+        .filter(|(_, task)| task.matrix.is_some())
+        .map(|(id, _)| id.clone())
+        .collect();
+    matrix_ids.sort();
+
+    for id in matrix_ids {
+        let task = graph.tasks.remove(&id).unwrap();
+
+        for values in combinations(&id, task.matrix.as_ref().unwrap())? {
+            let variant_id = variant_id(&id, &values);
+            if graph.tasks.contains_key(&variant_id) {
+                anyhow::bail!(
+                    "Matrix task '{}' expands to '{}', which is already defined",
+                    id,
+                    variant_id
+                );
+            }
+            let variant = instantiate(&id, &task, values)?;
+            graph.tasks.insert(variant_id, variant);
+        }
+    }
+
+    // Depending on a group means depending on all of its variants
+    // (including groups expanded earlier, e.g. in an included file)
+    let groups = graph.matrix_groups();
+    if groups.is_empty() {
+        return Ok(());
+    }
+    for task in graph.tasks.values_mut() {
+        let Some(deps) = &task.depends_on else {
+            continue;
+        };
+        let mut expanded: Vec<String> = Vec::new();
+        for dep in deps {
+            let targets = groups.get(dep).cloned().unwrap_or_else(|| vec![dep.clone()]);
+            for target in targets {
+                if !expanded.contains(&target) {
+                    expanded.push(target);
+                }
+            }
+        }
+        task.depends_on = Some(expanded);
+    }
+
+    Ok(())
+}
+
+/// ID of one variant, e.g. `train[lr=1e-3,python=3.10]`
+pub fn variant_id(group: &str, values: &BTreeMap<String, String>) -> String {
+    let params: Vec<String> = values.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    format!("{}[{}]", group, params.join(","))
+}
+
+/// Every combination of matrix values, in key then value order
+fn combinations(
+    id: &str,
+    matrix: &BTreeMap<String, Vec<String>>,
+) -> Result<Vec<BTreeMap<String, String>>> {
+    if matrix.is_empty() {
+        anyhow::bail!("Matrix task '{}' has an empty matrix", id);
+    }
+
+    let mut combos = vec![BTreeMap::new()];
+    for (key, values) in matrix {
+        if values.is_empty() {
+            anyhow::bail!("Matrix task '{}' has no values for '{}'", id, key);
+        }
+        combos = combos
+            .into_iter()
+            .flat_map(|combo| {
+                values.iter().map(move |value| {
+                    let mut combo = combo.clone();
+                    combo.insert(key.clone(), value.clone());
+                    combo
+                })
+            })
+            .collect();
+    }
+    Ok(combos)
+}
+
+/// Build one variant: substitute `${matrix.key}` and record the values
+fn instantiate(group: &str, task: &Task, values: BTreeMap<String, String>) -> Result<Task> {
+    let interpolate = |text: &str| -> Result<String> {
+        if let Some(caps) = MATRIX_VAR.captures_iter(text).find(|c| !values.contains_key(&c[1])) {
+            anyhow::bail!(
+                "Matrix task '{}' uses unknown matrix key '{}'",
+                group,
+                &caps[1]
+            );
+        }
+        Ok(MATRIX_VAR
+            .replace_all(text, |caps: &regex::Captures| values[&caps[1]].clone())
+            .into_owned())
+    };
+
+    let mut variant = task.clone();
+    variant.matrix = None;
+    variant.try_rewrite_fields(&interpolate)?;
+    if let Some(deps) = &task.depends_on {
+        variant.depends_on = Some(deps.iter().map(|d| interpolate(d)).collect::<Result<_>>()?);
+    }
+
+    variant.matrix_group = Some(group.to_string());
+    variant.matrix_values = Some(values);
+    Ok(variant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(yaml: &str) -> Result<Graph> {
+        let mut graph: Graph = serde_yaml::from_str(yaml).unwrap();
+        expand(&mut graph)?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn test_expand_matrix() {
+        let graph = expanded(
+            r#"
+tasks:
+  prep: { description: Prep, command: "make data" }
+  train:
+    description: "Train lr=${matrix.lr}"
+    command: "python train.py --lr ${matrix.lr} --python ${matrix.python}"
+    depends_on: [prep]
+    matrix:
+      lr: [1e-3, 1e-4]
+      python: [3.10, 3.11]
+  report: { description: Report, command: "make report", depends_on: [train] }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(graph.tasks.len(), 6);
+        assert!(!graph.tasks.contains_key("train"));
+
+        let variant = &graph.tasks["train[lr=1e-3,python=3.10]"];
+        assert_eq!(
+            variant.command.as_deref(),
+            Some("python train.py --lr 1e-3 --python 3.10")
+        );
+        assert_eq!(variant.description, "Train lr=1e-3");
+        assert_eq!(variant.matrix_group.as_deref(), Some("train"));
+        assert_eq!(variant.depends_on, Some(vec!["prep".to_string()]));
+
+        // The group dependency becomes all variants
+        assert_eq!(
+            graph.tasks["report"].depends_on,
+            Some(vec![
+                "train[lr=1e-3,python=3.10]".to_string(),
+                "train[lr=1e-3,python=3.11]".to_string(),
+                "train[lr=1e-4,python=3.10]".to_string(),
+                "train[lr=1e-4,python=3.11]".to_string(),
+            ])
+        );
+        assert!(graph.validate().is_ok());
+    }
+
+    #[test]
+    fn test_matrix_service_fields() {
+        let graph = expanded(
+            r#"
+tasks:
+  serve:
+    description: Serve
+    command: "python -m http.server ${matrix.port}"
+    kind: service
+    ready_when: { tcp: "${matrix.port}", http: "http://localhost:${matrix.port}/" }
+    semantic_commands: { ping: "curl localhost:${matrix.port}" }
+    matrix: { port: [8001, 8002] }
+"#,
+        )
+        .unwrap();
+
+        let variant = &graph.tasks["serve[port=8001]"];
+        let ready_when = variant.ready_when.as_ref().unwrap();
+        assert_eq!(ready_when.tcp.as_deref(), Some("8001"));
+        assert_eq!(ready_when.http.as_deref(), Some("http://localhost:8001/"));
+        assert_eq!(variant.semantic_commands.as_ref().unwrap()["ping"], "curl localhost:8001");
+        assert!(graph.validate().is_ok(), "{:?}", graph.validate().issues);
+    }
+
+    #[test]
+    fn test_matrix_errors() {
+        let unknown_key = expanded(
+            r#"
+tasks:
+  test: { description: Test, command: "tox -e ${matrix.py}", matrix: { python: ["3.11"] } }
+"#,
+        );
+        assert!(unknown_key.unwrap_err().to_string().contains("unknown matrix key 'py'"));
+
+        let empty = expanded(
+            r#"
+tasks:
+  test: { description: Test, command: "tox", matrix: { python: [] } }
+"#,
+        );
+        assert!(empty.is_err());
+    }
+}
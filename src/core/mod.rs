@@ -1,11 +1,48 @@
 //! Core engine - graph parsing, PTY management, task scheduling
 
+mod cache;
+mod capture;
+mod duration;
+mod env;
+mod format;
 mod graph;
+mod include;
+mod interpolate;
+mod matrix;
+mod plan;
+mod policy;
+mod probe;
 mod pty;
+mod restart;
 mod scheduler;
+mod select;
 mod executor;
+mod retry;
+mod validation;
+mod watch;
+mod writeback;
 
-pub use graph::{Graph, GraphTaskStatus, Metadata, Node, Task};
-pub use pty::{ExitResult, PTYHandle};
-pub use scheduler::Scheduler;
-pub use executor::{Executor, TaskEvent};
+pub use graph::{
+    split_project_ref, Graph, GraphTaskStatus, Metadata, Node, RunCondition, Task, TaskKind,
+};
+pub use cache::TaskCache;
+pub use capture::{OutputCapture, OUTPUT_FILE_ENV};
+pub use duration::HumanDuration;
+pub use env::{load_env_file, parse_env};
+pub use format::GraphFormat;
+pub use include::Include;
+pub use interpolate::{rename_output_sources, resolve_task, VarContext};
+pub use plan::{EstimateSource, FinishedTask, Plan, PlannedTask};
+pub use policy::{
+    CriticalPathPolicy, FairSharePolicy, PolicyKind, PriorityPolicy, SchedulingConfig, SchedulingPolicy,
+};
+pub use probe::{ReadinessProbe, ReadyWhen};
+pub use pty::{ExitResult, PTYHandle, Shell, Signal, SpawnOptions};
+pub use restart::{RestartHistory, RestartMode, RestartPolicy};
+pub use retry::RetryPolicy;
+pub use scheduler::{GraphDiff, Scheduler};
+pub use select::TaskSelection;
+pub use executor::{Executor, TaskEvent, DEFAULT_KILL_GRACE};
+pub use validation::{IssueSeverity, ValidationIssue, ValidationReport};
+pub use watch::FileWatcher;
+pub use writeback::StatusWriter;
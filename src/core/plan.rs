@@ -0,0 +1,358 @@
+//! Execution plan - what `gidterm run` would do, without running anything
+//!
+//! The plan replays the scheduler as if every task succeeded: each wave is
+//! the set of tasks that would start together. Durations come from past
+//! sessions when available, else from `estimated_hours`; the estimated total
+//! is the length of the critical path, since independent tasks run in
+//! parallel. The graph's `scheduling:` section applies, so with
+//! `max_parallel` a wave holds at most that many tasks.
+
+use super::{Graph, GraphTaskStatus, Scheduler, SchedulingConfig};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Where a task's duration estimate comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateSource {
+    /// Average of its successful runs in past sessions
+    History,
+    /// `estimated_hours` in the graph
+    EstimatedHours,
+}
+
+/// A task the run would start
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedTask {
+    pub id: String,
+    pub description: String,
+    /// Waits for approval instead of running
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub gate: bool,
+    /// Keeps running; dependents start once it is ready
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub service: bool,
+    pub estimate_secs: Option<u64>,
+    pub estimate_source: Option<EstimateSource>,
+}
+
+/// A task that will not run because of its current status
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FinishedTask {
+    pub id: String,
+    pub status: GraphTaskStatus,
+}
+
+/// Execution plan of a graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Plan {
+    /// Tasks started together, in order
+    pub waves: Vec<Vec<PlannedTask>>,
+    /// Already done, cached (or otherwise finished) before the run
+    pub finished: Vec<FinishedTask>,
+    /// Not run because of how their dependencies finish (`on:` conditions)
+    pub skipped: Vec<String>,
+    /// Never started: they wait on a task that will not finish
+    pub blocked: Vec<String>,
+    /// Longest chain of tasks by estimated duration
+    pub critical_path: Vec<String>,
+    /// Length of the critical path
+    pub estimated_secs: u64,
+    /// Tasks to run with no estimate (gates and services excluded)
+    pub unestimated: Vec<String>,
+}
+
+impl Plan {
+    /// Plan a run of the graph; `history` holds average durations of past runs
+    pub fn build(graph: &Graph, history: &HashMap<String, Duration>) -> Self {
+        let mut finished: Vec<FinishedTask> = graph
+            .tasks
+            .iter()
+            .filter(|(_, task)| {
+                !matches!(task.status, GraphTaskStatus::Pending | GraphTaskStatus::Planned)
+            })
+            .map(|(id, task)| FinishedTask {
+                id: id.clone(),
+                status: task.status.clone(),
+            })
+            .collect();
+        finished.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut scheduler = Scheduler::new(graph.clone());
+        scheduler.configure(&SchedulingConfig::default(), history);
+        let mut waves = Vec::new();
+        let mut skipped = Vec::new();
+        loop {
+            let mut wave = scheduler.schedule_next();
+            let gates = scheduler.take_awaiting_approval();
+            skipped.extend(scheduler.take_skipped());
+            if wave.is_empty() && gates.is_empty() {
+                break;
+            }
+            wave.extend(gates.iter().cloned());
+            wave.sort();
+
+            for id in &wave {
+                let result = if gates.contains(id) {
+                    scheduler.approve(id)
+                } else {
+                    scheduler.mark_started(id).and_then(|_| scheduler.mark_done(id))
+                };
+                if let Err(e) = result {
+                    log::warn!("Failed to plan task {}: {}", id, e);
+                }
+            }
+            waves.push(wave.iter().map(|id| planned_task(graph, id, history)).collect::<Vec<_>>());
+        }
+        skipped.extend(scheduler.take_skipped());
+        skipped.sort();
+
+        let mut blocked: Vec<String> = scheduler
+            .graph()
+            .all_tasks()
+            .iter()
+            .filter(|(_, task)| matches!(task.status, GraphTaskStatus::Pending | GraphTaskStatus::Planned))
+            .map(|(id, _)| id.clone())
+            .collect();
+        blocked.sort();
+
+        let (critical_path, estimated_secs) = critical_path(graph, &waves);
+        let unestimated = waves
+            .iter()
+            .flatten()
+            .filter(|t| t.estimate_secs.is_none() && !t.gate && !t.service)
+            .map(|t| t.id.clone())
+            .collect();
+
+        Self {
+            waves,
+            finished,
+            skipped,
+            blocked,
+            critical_path,
+            estimated_secs,
+            unestimated,
+        }
+    }
+
+    /// Number of tasks the run would start
+    pub fn task_count(&self) -> usize {
+        self.waves.iter().map(Vec::len).sum()
+    }
+}
+
+fn planned_task(graph: &Graph, id: &str, history: &HashMap<String, Duration>) -> PlannedTask {
+    let task = &graph.tasks[id];
+    let gate = task.is_gate();
+    let service = task.is_service();
+
+    // A service's lifetime says nothing about how long it takes to be ready
+    let (estimate_secs, estimate_source) = match (history.get(id), task.estimated_hours) {
+        _ if gate || service => (None, None),
+        (Some(duration), _) => (Some(duration.as_secs()), Some(EstimateSource::History)),
+        (None, Some(hours)) => (Some(u64::from(hours) * 3600), Some(EstimateSource::EstimatedHours)),
+        (None, None) => (None, None),
+    };
+    PlannedTask {
+        id: id.to_string(),
+        description: task.description.clone(),
+        gate,
+        service,
+        estimate_secs,
+        estimate_source,
+    }
+}
+
+/// Longest path through the planned tasks by estimated duration (ties go to
+/// the longer chain); returns the path and its duration in seconds
+fn critical_path(graph: &Graph, waves: &[Vec<PlannedTask>]) -> (Vec<String>, u64) {
+    // task -> (finish time, chain length, predecessor)
+    let mut finish: HashMap<&str, (u64, usize, Option<&str>)> = HashMap::new();
+    for task in waves.iter().flatten() {
+        let mut start = (0, 0, None);
+        for dep in graph.tasks[&task.id].depends_on.iter().flatten() {
+            if let Some(&(time, len, _)) = finish.get(dep.as_str()) {
+                if (time, len) > (start.0, start.1) {
+                    start = (time, len, Some(dep.as_str()));
+                }
+            }
+        }
+        let time = start.0 + task.estimate_secs.unwrap_or(0);
+        finish.insert(task.id.as_str(), (time, start.1 + 1, start.2));
+    }
+
+    let mut end: Option<(&str, u64, usize)> = None;
+    for task in waves.iter().flatten() {
+        let (time, len, _) = finish[task.id.as_str()];
+        if end.is_none_or(|(_, t, l)| (time, len) > (t, l)) {
+            end = Some((task.id.as_str(), time, len));
+        }
+    }
+    let Some((last, total, _)) = end else {
+        return (Vec::new(), 0);
+    };
+
+    let mut path = vec![last.to_string()];
+    let mut current = last;
+    while let Some(prev) = finish[current].2 {
+        path.push(prev.to_string());
+        current = prev;
+    }
+    path.reverse();
+    (path, total)
+}
+
+/// Human-readable duration, e.g. `45s`, `12m30s`, `3h05m`
+fn format_duration(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.finished.is_empty() {
+            let finished: Vec<String> = self
+                .finished
+                .iter()
+                .map(|t| format!("{} ({})", t.id, t.status))
+                .collect();
+            writeln!(f, "Already finished: {}", finished.join(", "))?;
+        }
+
+        let width = self.waves.iter().flatten().map(|t| t.id.len()).max().unwrap_or(0);
+        for (idx, wave) in self.waves.iter().enumerate() {
+            writeln!(f, "Wave {}:", idx + 1)?;
+            for task in wave {
+                let estimate = if task.gate {
+                    "waits for approval".to_string()
+                } else if task.service {
+                    "service".to_string()
+                } else {
+                    match (task.estimate_secs, task.estimate_source) {
+                        (Some(secs), Some(EstimateSource::History)) => {
+                            format!("~{} (history)", format_duration(secs))
+                        }
+                        (Some(secs), _) => format!("~{} (estimated)", format_duration(secs)),
+                        (None, _) => "no estimate".to_string(),
+                    }
+                };
+                let marker = if self.critical_path.contains(&task.id) { "*" } else { " " };
+                writeln!(f, " {} {:<width$}  {}", marker, task.id, estimate, width = width)?;
+            }
+        }
+        if self.waves.is_empty() {
+            writeln!(f, "Nothing to run.")?;
+        }
+
+        if !self.skipped.is_empty() {
+            writeln!(f, "Skipped: {}", self.skipped.join(", "))?;
+        }
+        if !self.blocked.is_empty() {
+            writeln!(f, "Blocked (waiting on tasks that will not finish): {}", self.blocked.join(", "))?;
+        }
+        if !self.critical_path.is_empty() {
+            writeln!(f, "Critical path (*): {}", self.critical_path.join(" → "))?;
+        }
+        write!(
+            f,
+            "{} task(s) in {} wave(s), estimated ~{}",
+            self.task_count(),
+            self.waves.len(),
+            format_duration(self.estimated_secs)
+        )?;
+        if !self.unestimated.is_empty() {
+            write!(f, " ({} task(s) without an estimate)", self.unestimated.len())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        serde_yaml::from_str(
+            r#"
+tasks:
+  install: { description: Install, command: npm ci, status: done }
+  build: { description: Build, command: npm run build, depends_on: [install], estimated_hours: 1 }
+  lint: { description: Lint, command: npm run lint, depends_on: [install] }
+  test: { description: Test, command: npm test, depends_on: [build] }
+  docs: { description: Docs, command: npm run docs, depends_on: [lint] }
+  approve: { description: Approve, type: gate, depends_on: [test, docs] }
+  deploy: { description: Deploy, command: ./deploy, depends_on: [approve] }
+  rollback: { description: Rollback, command: ./rollback, depends_on: [deploy], on: failure }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_waves_and_critical_path() {
+        let history = HashMap::from([
+            ("test".to_string(), Duration::from_secs(600)),
+            ("lint".to_string(), Duration::from_secs(30)),
+            ("docs".to_string(), Duration::from_secs(7200)),
+        ]);
+        let plan = Plan::build(&graph(), &history);
+
+        let waves: Vec<Vec<&str>> = plan
+            .waves
+            .iter()
+            .map(|w| w.iter().map(|t| t.id.as_str()).collect())
+            .collect();
+        assert_eq!(
+            waves,
+            vec![vec!["build", "lint"], vec!["docs", "test"], vec!["approve"], vec!["deploy"]]
+        );
+        assert_eq!(plan.finished, vec![FinishedTask { id: "install".into(), status: GraphTaskStatus::Done }]);
+        assert_eq!(plan.skipped, vec!["rollback"]);
+        assert!(plan.blocked.is_empty());
+
+        // lint + docs (2h00m30s) beats build + test (1h10m)
+        assert_eq!(plan.critical_path, vec!["lint", "docs", "approve", "deploy"]);
+        assert_eq!(plan.estimated_secs, 7230);
+        assert_eq!(plan.unestimated, vec!["deploy"]);
+
+        let build = &plan.waves[0][0];
+        assert_eq!(build.estimate_secs, Some(3600));
+        assert_eq!(build.estimate_source, Some(EstimateSource::EstimatedHours));
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["waves"][2][0]["gate"], true);
+        assert_eq!(json["finished"][0]["status"], "done");
+        assert!(plan.to_string().contains("Critical path (*): lint → docs → approve → deploy"));
+    }
+
+    #[test]
+    fn test_blocked_tasks() {
+        let graph: Graph = serde_yaml::from_str(
+            r#"
+tasks:
+  migrate: { description: Migrate, command: ./migrate, status: planned }
+  seed: { description: Seed, command: ./seed, depends_on: [migrate] }
+"#,
+        )
+        .unwrap();
+        let plan = Plan::build(&graph, &HashMap::new());
+        assert!(plan.waves.is_empty());
+        assert_eq!(plan.blocked, vec!["migrate", "seed"]);
+        assert_eq!(plan.estimated_secs, 0);
+
+        // A task a stopped run left in progress runs again
+        let mut graph = graph;
+        graph.tasks.get_mut("migrate").unwrap().status = GraphTaskStatus::InProgress;
+        graph.reset_interrupted();
+        let plan = Plan::build(&graph, &HashMap::new());
+        assert_eq!(plan.waves.len(), 2);
+        assert!(plan.blocked.is_empty());
+    }
+}
@@ -0,0 +1,350 @@
+//! Scheduling policies - which ready tasks start first
+//!
+//! ```yaml
+//! scheduling:
+//!   policy: critical-path   # priority (default), critical-path or fair-share
+//!   max_parallel: 4         # tasks running at once (default: no limit)
+//! ```
+//!
+//! Without `max_parallel` every ready task starts right away and the policy
+//! only decides the start order; with a limit it decides which tasks get the
+//! free slots. `gidterm run --policy ... --max-parallel ...` overrides the
+//! graph.
+
+use super::{Graph, GraphTaskStatus, Task};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Orders the tasks that are ready to start
+pub trait SchedulingPolicy: Send {
+    /// Name shown in logs, e.g. `critical-path`
+    fn name(&self) -> &'static str;
+
+    /// Sort `ready` (given sorted by ID) so the tasks to start first come first
+    fn order(&mut self, graph: &Graph, ready: &mut Vec<String>);
+}
+
+/// Built-in scheduling policies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PolicyKind {
+    /// Highest `priority:` first (critical, high, medium, low)
+    #[default]
+    Priority,
+    /// Longest remaining chain of work first, by past run times
+    CriticalPath,
+    /// Take turns between workspace projects
+    FairShare,
+}
+
+impl PolicyKind {
+    /// Create the policy; `history` holds average durations of past runs
+    pub fn build(self, history: &HashMap<String, Duration>) -> Box<dyn SchedulingPolicy> {
+        match self {
+            Self::Priority => Box::new(PriorityPolicy),
+            Self::CriticalPath => Box::new(CriticalPathPolicy::new(history.clone())),
+            Self::FairShare => Box::new(FairSharePolicy::default()),
+        }
+    }
+}
+
+impl fmt::Display for PolicyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Priority => "priority",
+            Self::CriticalPath => "critical-path",
+            Self::FairShare => "fair-share",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for PolicyKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "priority" => Ok(Self::Priority),
+            "critical-path" => Ok(Self::CriticalPath),
+            "fair-share" => Ok(Self::FairShare),
+            _ => anyhow::bail!(
+                "unknown scheduling policy '{}' (expected priority, critical-path or fair-share)",
+                s
+            ),
+        }
+    }
+}
+
+/// `scheduling:` section of a graph
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulingConfig {
+    /// Policy ordering ready tasks (default: priority)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyKind>,
+    /// Tasks running at once (default: no limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel: Option<NonZeroUsize>,
+}
+
+impl SchedulingConfig {
+    /// This config, with unset fields taken from `fallback`
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            policy: self.policy.or(fallback.policy),
+            max_parallel: self.max_parallel.or(fallback.max_parallel),
+        }
+    }
+}
+
+/// Rank of a task's `priority:`, lowest first; unset counts as medium
+fn priority_rank(task: Option<&Task>) -> u8 {
+    match task.and_then(|t| t.priority.as_deref()) {
+        Some("critical") => 0,
+        Some("high") => 1,
+        Some("medium") | None => 2,
+        Some(_) => 3,
+    }
+}
+
+/// Highest priority first, then by ID
+#[derive(Debug, Default)]
+pub struct PriorityPolicy;
+
+impl SchedulingPolicy for PriorityPolicy {
+    fn name(&self) -> &'static str {
+        "priority"
+    }
+
+    fn order(&mut self, graph: &Graph, ready: &mut Vec<String>) {
+        ready.sort_by_key(|id| priority_rank(graph.get_task(id)));
+    }
+}
+
+/// Tasks heading the longest remaining chain of work first
+///
+/// A task's weight is its average duration in past sessions, else its
+/// `estimated_hours`; ties go to the longer chain, then to priority.
+#[derive(Debug, Default)]
+pub struct CriticalPathPolicy {
+    durations: HashMap<String, Duration>,
+}
+
+impl CriticalPathPolicy {
+    pub fn new(durations: HashMap<String, Duration>) -> Self {
+        Self { durations }
+    }
+
+    fn estimate_secs(&self, id: &str, task: &Task) -> u64 {
+        // Gates wait on people and services never finish
+        if task.is_gate() || task.is_service() {
+            return 0;
+        }
+        match (self.durations.get(id), task.estimated_hours) {
+            (Some(duration), _) => duration.as_secs(),
+            (None, Some(hours)) => u64::from(hours) * 3600,
+            (None, None) => 0,
+        }
+    }
+
+    /// Estimated time and length of the longest chain starting at `id`
+    fn remaining<'a>(
+        &self,
+        graph: &'a Graph,
+        dependents: &HashMap<&'a str, Vec<&'a str>>,
+        memo: &mut HashMap<&'a str, (u64, usize)>,
+        id: &'a str,
+    ) -> (u64, usize) {
+        if let Some(&cached) = memo.get(id) {
+            return cached;
+        }
+        // Placeholder in case of a cycle
+        memo.insert(id, (0, 0));
+
+        let mut longest = (0, 0);
+        for &dependent in dependents.get(id).into_iter().flatten() {
+            longest = longest.max(self.remaining(graph, dependents, memo, dependent));
+        }
+        let own = graph.tasks.get(id).map_or(0, |task| self.estimate_secs(id, task));
+        let result = (own + longest.0, longest.1 + 1);
+        memo.insert(id, result);
+        result
+    }
+}
+
+impl SchedulingPolicy for CriticalPathPolicy {
+    fn name(&self) -> &'static str {
+        "critical-path"
+    }
+
+    fn order(&mut self, graph: &Graph, ready: &mut Vec<String>) {
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (id, task) in &graph.tasks {
+            if !matches!(task.status, GraphTaskStatus::Pending | GraphTaskStatus::Planned) {
+                continue;
+            }
+            for dep in task.depends_on.iter().flatten() {
+                dependents.entry(dep.as_str()).or_default().push(id.as_str());
+            }
+        }
+
+        let mut memo = HashMap::new();
+        let ranks: HashMap<String, ((u64, usize), u8)> = ready
+            .iter()
+            .map(|id| {
+                let Some((key, _)) = graph.tasks.get_key_value(id) else {
+                    return (id.clone(), ((0, 0), priority_rank(None)));
+                };
+                let remaining = self.remaining(graph, &dependents, &mut memo, key);
+                (id.clone(), (remaining, priority_rank(graph.get_task(id))))
+            })
+            .collect();
+        ready.sort_by(|a, b| {
+            let (a_path, a_priority) = ranks[a];
+            let (b_path, b_priority) = ranks[b];
+            b_path.cmp(&a_path).then(a_priority.cmp(&b_priority))
+        });
+    }
+}
+
+/// Round-robin between workspace projects
+///
+/// Projects with the fewest running tasks go first; ties rotate, starting
+/// after the project served first last time. Each project's tasks are taken
+/// in priority order.
+#[derive(Debug, Default)]
+pub struct FairSharePolicy {
+    /// Project of the task put first on the previous call
+    last: Option<Option<PathBuf>>,
+}
+
+impl SchedulingPolicy for FairSharePolicy {
+    fn name(&self) -> &'static str {
+        "fair-share"
+    }
+
+    fn order(&mut self, graph: &Graph, ready: &mut Vec<String>) {
+        PriorityPolicy.order(graph, ready);
+
+        // Projects are told apart by root (unset outside workspace mode)
+        let project = |id: &str| graph.get_task(id).and_then(|t| t.root.clone());
+        let mut queues: BTreeMap<Option<PathBuf>, VecDeque<String>> = BTreeMap::new();
+        for id in ready.drain(..) {
+            queues.entry(project(&id)).or_default().push_back(id);
+        }
+
+        let mut running: HashMap<Option<PathBuf>, usize> = HashMap::new();
+        for task in graph.tasks.values() {
+            if task.status == GraphTaskStatus::InProgress {
+                *running.entry(task.root.clone()).or_default() += 1;
+            }
+        }
+
+        let projects: Vec<Option<PathBuf>> = queues.keys().cloned().collect();
+        let start = match &self.last {
+            Some(last) => projects.iter().position(|p| p > last).unwrap_or(0),
+            None => 0,
+        };
+        let mut turns: Vec<(usize, usize, Option<PathBuf>)> = projects
+            .into_iter()
+            .enumerate()
+            .map(|(idx, p)| {
+                let rotation = (idx + queues.len() - start) % queues.len();
+                (running.get(&p).copied().unwrap_or(0), rotation, p)
+            })
+            .collect();
+        turns.sort();
+
+        while !queues.is_empty() {
+            for (_, _, p) in &turns {
+                let Some(queue) = queues.get_mut(p) else {
+                    continue;
+                };
+                ready.extend(queue.pop_front());
+                if queue.is_empty() {
+                    queues.remove(p);
+                }
+            }
+        }
+        if let Some(first) = ready.first() {
+            self.last = Some(project(first));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        serde_yaml::from_str(
+            r#"
+tasks:
+  docs: { description: Docs, command: npm run docs }
+  lint: { description: Lint, command: npm run lint, priority: low }
+  build: { description: Build, command: npm run build, estimated_hours: 1 }
+  test: { description: Test, command: npm test, depends_on: [build] }
+  e2e: { description: E2E, command: npm run e2e, depends_on: [docs], priority: critical }
+  hotfix: { description: Hotfix, command: ./hotfix, priority: high }
+"#,
+        )
+        .unwrap()
+    }
+
+    fn order(policy: &mut dyn SchedulingPolicy, graph: &Graph) -> Vec<String> {
+        let mut ready = graph.get_ready_tasks();
+        policy.order(graph, &mut ready);
+        ready
+    }
+
+    #[test]
+    fn test_priority_and_critical_path() {
+        let graph = graph();
+        assert_eq!(graph.get_ready_tasks(), vec!["build", "docs", "hotfix", "lint"]);
+        assert_eq!(order(&mut PriorityPolicy, &graph), vec!["hotfix", "build", "docs", "lint"]);
+
+        // build + test (1h) without history; docs + e2e (2h) with it
+        let mut policy = CriticalPathPolicy::default();
+        assert_eq!(order(&mut policy, &graph), vec!["build", "docs", "hotfix", "lint"]);
+        let history = HashMap::from([
+            ("e2e".to_string(), Duration::from_secs(7000)),
+            ("docs".to_string(), Duration::from_secs(200)),
+        ]);
+        let mut policy = PolicyKind::CriticalPath.build(&history);
+        assert_eq!(order(policy.as_mut(), &graph), vec!["docs", "build", "hotfix", "lint"]);
+
+        assert_eq!("fair-share".parse::<PolicyKind>().unwrap(), PolicyKind::FairShare);
+        assert!("fastest".parse::<PolicyKind>().is_err());
+    }
+
+    #[test]
+    fn test_fair_share_rotates_projects() {
+        let mut graph: Graph = serde_yaml::from_str(
+            r#"
+tasks:
+  "api:build": { description: Build, command: cargo build }
+  "api:lint": { description: Lint, command: cargo clippy, priority: high }
+  "api:test": { description: Test, command: cargo test }
+  "web:build": { description: Build, command: npm run build }
+"#,
+        )
+        .unwrap();
+        for (id, task) in &mut graph.tasks {
+            task.root = Some(PathBuf::from(id.split(':').next().unwrap()));
+        }
+
+        let mut policy = FairSharePolicy::default();
+        assert_eq!(order(&mut policy, &graph), vec!["api:lint", "web:build", "api:build", "api:test"]);
+        // web goes first on the next tie
+        assert_eq!(order(&mut policy, &graph), vec!["web:build", "api:lint", "api:build", "api:test"]);
+
+        // Projects with fewer running tasks go first
+        graph.update_task_status("api:lint", GraphTaskStatus::InProgress).unwrap();
+        let mut policy = FairSharePolicy::default();
+        assert_eq!(order(&mut policy, &graph), vec!["web:build", "api:build", "api:test"]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Readiness probes for service tasks
+//!
+//! ```yaml
+//! dev:
+//!   kind: service
+//!   command: npm run dev
+//!   ready_when:
+//!     tcp: 3000                      # port (or host:port) accepts connections
+//!     output: "Listening on"         # regex over the service's output
+//!     http: http://localhost:3000/   # local URL answers with a 2xx status
+//!     file: tmp/ready                # file appears (relative to cwd)
+//! ```
+//!
+//! Every listed condition must hold. Once it does, the service is `ready`
+//! and its dependents start while it keeps running.
+
+use super::duration::HumanDuration;
+use super::format;
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Default time between probe attempts
+pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Time allowed for a single TCP/HTTP probe
+const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `ready_when:` as written in the graph
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadyWhen {
+    /// Port (or `host:port`) that accepts TCP connections
+    #[serde(default, deserialize_with = "format::deserialize_scalar")]
+    pub tcp: Option<String>,
+    /// Regex matched against the service's output
+    pub output: Option<String>,
+    /// Local `http://` URL that answers with a 2xx status
+    pub http: Option<String>,
+    /// File that appears once the service is up (relative to the task's cwd)
+    pub file: Option<String>,
+    /// Time between probe attempts (default 500ms)
+    pub interval: Option<HumanDuration>,
+    /// Fail the service if it is not ready within this time
+    pub timeout: Option<HumanDuration>,
+}
+
+impl ReadyWhen {
+    /// The text fields, which may contain `${...}` variables
+    pub fn fields_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        [&mut self.tcp, &mut self.output, &mut self.http, &mut self.file]
+            .into_iter()
+            .flatten()
+    }
+}
+
+/// A compiled `ready_when`, checked by the executor
+#[derive(Debug, Clone)]
+pub struct ReadinessProbe {
+    tcp: Option<String>,
+    output: Option<Regex>,
+    http: Option<HttpTarget>,
+    file: Option<PathBuf>,
+    pub interval: Duration,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+struct HttpTarget {
+    /// `host:port` to connect to
+    address: String,
+    host: String,
+    path: String,
+}
+
+impl ReadinessProbe {
+    /// Compile a `ready_when`; relative files are resolved against `cwd`
+    pub fn new(ready_when: &ReadyWhen, cwd: Option<&Path>) -> Result<Self> {
+        let tcp = ready_when.tcp.as_deref().map(|target| {
+            let target = target.trim();
+            if target.contains(':') {
+                target.to_string()
+            } else {
+                format!("127.0.0.1:{}", target)
+            }
+        });
+        let output = ready_when
+            .output
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid ready_when output pattern: {}", e))
+            })
+            .transpose()?;
+        let http = ready_when.http.as_deref().map(parse_http_url).transpose()?;
+        let file = ready_when.file.as_deref().map(|file| match cwd {
+            Some(dir) => dir.join(file),
+            None => PathBuf::from(file),
+        });
+
+        Ok(Self {
+            tcp,
+            output,
+            http,
+            file,
+            interval: ready_when
+                .interval
+                .map(|i| i.as_duration())
+                .unwrap_or(DEFAULT_PROBE_INTERVAL),
+            timeout: ready_when.timeout.map(|t| t.as_duration()),
+        })
+    }
+
+    /// True if readiness depends on the service's output
+    pub fn watches_output(&self) -> bool {
+        self.output.is_some()
+    }
+
+    /// True if this output line satisfies the `output` condition
+    pub fn matches_output(&self, line: &str) -> bool {
+        self.output.as_ref().is_some_and(|re| re.is_match(line))
+    }
+
+    /// Check the polled conditions (`tcp`, `http`, `file`)
+    pub async fn check(&self) -> bool {
+        if let Some(file) = &self.file {
+            if !file.exists() {
+                return false;
+            }
+        }
+        if let Some(address) = &self.tcp {
+            if !tcp_open(address).await {
+                return false;
+            }
+        }
+        if let Some(target) = &self.http {
+            if !http_ok(target).await {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Split `http://host[:port][/path]`
+fn parse_http_url(url: &str) -> Result<HttpTarget> {
+    let Some(rest) = url.trim().strip_prefix("http://") else {
+        anyhow::bail!("ready_when http must be an http:// URL: {}", url);
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        anyhow::bail!("ready_when http URL has no host: {}", url);
+    }
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    Ok(HttpTarget {
+        address,
+        host: authority.to_string(),
+        path: path.to_string(),
+    })
+}
+
+async fn tcp_open(address: &str) -> bool {
+    matches!(
+        tokio::time::timeout(PROBE_CONNECT_TIMEOUT, TcpStream::connect(address)).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Minimal HTTP/1.0 GET, true on a 2xx status
+async fn http_ok(target: &HttpTarget) -> bool {
+    let request = async {
+        let mut stream = TcpStream::connect(&target.address).await?;
+        let request = format!(
+            "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            target.path, target.host
+        );
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut head = [0u8; 32];
+        let n = stream.read(&mut head).await?;
+        Ok::<_, std::io::Error>(String::from_utf8_lossy(&head[..n]).into_owned())
+    };
+
+    match tokio::time::timeout(PROBE_CONNECT_TIMEOUT, request).await {
+        Ok(Ok(status_line)) => status_line
+            .split_whitespace()
+            .nth(1)
+            .is_some_and(|code| code.starts_with('2') && code.len() == 3),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tcp_http_and_file_probes() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 256];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket.write_all(b"HTTP/1.0 200 OK\r\n\r\nok").await;
+            }
+        });
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let ready_when = ReadyWhen {
+            tcp: Some(port.to_string()),
+            http: Some(format!("http://127.0.0.1:{}/health", port)),
+            file: Some("ready".to_string()),
+            ..Default::default()
+        };
+        let probe = ReadinessProbe::new(&ready_when, Some(dir.path())).unwrap();
+        assert!(!probe.check().await);
+
+        std::fs::write(dir.path().join("ready"), "").unwrap();
+        assert!(probe.check().await);
+    }
+
+    #[test]
+    fn test_probe_config() {
+        let ready_when: ReadyWhen =
+            serde_yaml::from_str("tcp: 3000\noutput: 'Listening on \\d+'\n").unwrap();
+        let probe = ReadinessProbe::new(&ready_when, None).unwrap();
+        assert_eq!(probe.tcp.as_deref(), Some("127.0.0.1:3000"));
+        assert!(probe.matches_output("Listening on 3000"));
+        assert!(!probe.matches_output("Compiling..."));
+
+        let https = ReadyWhen {
+            http: Some("https://localhost/".to_string()),
+            ..Default::default()
+        };
+        assert!(ReadinessProbe::new(&https, None).is_err());
+    }
+}
@@ -1,17 +1,88 @@
 //! PTY (pseudo-terminal) management - spawn and monitor processes
 
+use super::probe::ReadinessProbe;
 use anyhow::Result;
 use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Output line limit per task
 const MAX_OUTPUT_LINES: usize = 1000;
 
+/// Shell used to interpret a task command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Shell {
+    /// `sh -c` (default)
+    #[default]
+    Sh,
+    /// `bash -c`
+    Bash,
+    /// `zsh -c`
+    Zsh,
+    /// The user's `$SHELL` as a login shell (`-l -c`), so profiles are sourced
+    Login,
+    /// Any other shell binary, invoked with `-c`
+    Custom(String),
+}
+
+impl Shell {
+    /// Parse a shell name from the graph (`sh`, `bash`, `zsh`, `login`, or a path)
+    pub fn from_name(name: &str) -> Self {
+        match name.trim() {
+            "" | "sh" => Self::Sh,
+            "bash" => Self::Bash,
+            "zsh" => Self::Zsh,
+            "login" => Self::Login,
+            other => Self::Custom(other.to_string()),
+        }
+    }
+
+    /// Full argv to run `command` with this shell
+    pub fn argv(&self, command: &str) -> Vec<String> {
+        let (program, flags): (String, &[&str]) = match self {
+            Self::Sh => ("sh".to_string(), &["-c"]),
+            Self::Bash => ("bash".to_string(), &["-c"]),
+            Self::Zsh => ("zsh".to_string(), &["-c"]),
+            Self::Login => (
+                std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string()),
+                &["-l", "-c"],
+            ),
+            Self::Custom(path) => (path.clone(), &["-c"]),
+        };
+
+        let mut argv = vec![program];
+        argv.extend(flags.iter().map(|f| f.to_string()));
+        argv.push(command.to_string());
+        argv
+    }
+}
+
+/// How to spawn a task process
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    /// Working directory (defaults to gidterm's cwd)
+    pub cwd: Option<PathBuf>,
+    /// Extra environment variables, layered over the inherited environment
+    pub env: HashMap<String, String>,
+    /// Shell that interprets the command
+    pub shell: Shell,
+    /// Maximum run time before the executor terminates the task
+    pub timeout: Option<Duration>,
+    /// Time between SIGTERM and SIGKILL on timeout (executor default if unset)
+    pub kill_grace: Option<Duration>,
+    /// Readiness probe for services (the executor reports `Ready` when it passes)
+    pub ready: Option<ReadinessProbe>,
+}
+
 /// PTY handle for a single task
 #[derive(Clone)]
 pub struct PTYHandle {
     pub id: String,
+    /// Time between SIGTERM and SIGKILL when the task is stopped
+    pub kill_grace: Option<Duration>,
     output_history: Arc<Mutex<Vec<String>>>,
     reader: Arc<Mutex<Option<BufReader<Box<dyn Read + Send>>>>>,
     child: Arc<Mutex<Option<Box<dyn Child + Send + Sync>>>>,
@@ -21,22 +92,31 @@ pub struct PTYHandle {
 impl PTYHandle {
     /// Spawn a new process in a PTY
     ///
-    /// Commands are wrapped in `sh -c "..."` to support:
+    /// Commands are wrapped in `sh -c "..."` (or the configured shell) to support:
     /// - Pipes: `cat file | grep foo`
     /// - Chaining: `cd dir && npm run dev`
     /// - Quoted args: `echo "hello world"`
     /// - Environment variables: `FOO=bar cmd`
-    pub fn spawn(task_id: &str, command: &str) -> Result<Self> {
+    pub fn spawn(task_id: &str, command: &str, options: &SpawnOptions) -> Result<Self> {
         log::info!("Spawning PTY for task {}: {}", task_id, command);
 
         if command.trim().is_empty() {
             anyhow::bail!("Empty command");
         }
 
-        // Wrap in sh -c for proper shell interpretation
-        let mut cmd = CommandBuilder::new("sh");
-        cmd.arg("-c");
-        cmd.arg(command);
+        // Wrap in a shell for proper interpretation
+        let argv = options.shell.argv(command);
+        let mut cmd = CommandBuilder::from_argv(argv.into_iter().map(Into::into).collect());
+
+        if let Some(cwd) = &options.cwd {
+            if !cwd.is_dir() {
+                anyhow::bail!("Working directory {} does not exist", cwd.display());
+            }
+            cmd.cwd(cwd);
+        }
+        for (key, value) in &options.env {
+            cmd.env(key, value);
+        }
 
         // Create PTY
         let pty_system = native_pty_system();
@@ -59,6 +139,7 @@ impl PTYHandle {
 
         Ok(Self {
             id: task_id.to_string(),
+            kill_grace: options.kill_grace,
             output_history: Arc::new(Mutex::new(Vec::new())),
             reader: Arc::new(Mutex::new(Some(buf_reader))),
             child: Arc::new(Mutex::new(Some(child))),
@@ -173,6 +254,46 @@ impl PTYHandle {
         Ok(())
     }
 
+    /// OS process ID of the spawned child (if still known)
+    pub fn pid(&self) -> Option<u32> {
+        let child_guard = self.child.lock().unwrap();
+        child_guard.as_ref().and_then(|child| child.process_id())
+    }
+
+    /// Send a signal to the task's whole process group
+    ///
+    /// The PTY child is a session leader, so its PID is also the group ID;
+    /// signalling the group reaches processes started by the shell too.
+    #[cfg(unix)]
+    pub fn signal_group(&self, signal: Signal) -> Result<()> {
+        let Some(pid) = self.pid().and_then(|pid| libc::pid_t::try_from(pid).ok()) else {
+            return Ok(());
+        };
+        let signo = match signal {
+            Signal::Term => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+        };
+        // SAFETY: killpg and kill take plain integers and touch no memory
+        if unsafe { libc::killpg(pid, signo) } != 0 {
+            // Fall back to the child alone
+            if unsafe { libc::kill(pid, signo) } != 0 {
+                let err = std::io::Error::last_os_error();
+                if err.raw_os_error() == Some(libc::ESRCH) {
+                    return Ok(()); // Already gone
+                }
+                anyhow::bail!("Failed to send {} to task {} (pid {}): {}", signal, self.id, pid, err);
+            }
+        }
+        log::info!("Sent {} to task {} (pid {})", signal, self.id, pid);
+        Ok(())
+    }
+
+    /// Signals are Unix-only; terminate the child directly elsewhere
+    #[cfg(not(unix))]
+    pub fn signal_group(&self, _signal: Signal) -> Result<()> {
+        self.kill()
+    }
+
     /// Check if process is still alive
     pub fn is_alive(&self) -> bool {
         let child_guard = self.child.lock().unwrap();
@@ -180,6 +301,24 @@ impl PTYHandle {
     }
 }
 
+/// Signal sent to a task's process group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ask the processes to exit (SIGTERM)
+    Term,
+    /// Kill them outright (SIGKILL)
+    Kill,
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Term => write!(f, "SIGTERM"),
+            Self::Kill => write!(f, "SIGKILL"),
+        }
+    }
+}
+
 /// Result from process exit
 #[derive(Debug, Clone)]
 pub struct ExitResult {
@@ -0,0 +1,158 @@
+//! Restart policies - keep services and workers running after they exit
+//!
+//! ```yaml
+//! worker:
+//!   kind: service
+//!   command: ./worker
+//!   restart: on-failure      # always, on-failure or never (default)
+//!   max_restarts: 5          # give up after 5 restarts (default: no limit)
+//!   restart_delay: 1s        # first restart after 1s, then 2s, 4s...
+//! ```
+//!
+//! Restarts back off exponentially while the process keeps crashing; a run
+//! that stays up for `RESTART_RESET_AFTER` starts the backoff over.
+
+use super::retry::backoff_delay;
+use super::Task;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Delay before the first restart when `restart_delay` is not set
+pub const DEFAULT_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Delay multiplier per restart when `restart_backoff` is not set
+pub const DEFAULT_RESTART_BACKOFF: f64 = 2.0;
+
+/// A run lasting this long resets the backoff to `restart_delay`
+pub const RESTART_RESET_AFTER: Duration = Duration::from_secs(60);
+
+/// Upper bound for a single backoff delay
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(300);
+
+/// When a task is restarted after its process exits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    /// Whenever the process exits, successfully or not
+    Always,
+    /// Only when the process fails
+    OnFailure,
+    #[default]
+    Never,
+}
+
+/// How a task is restarted
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    /// Restarts allowed in total (`None` = no limit)
+    pub max_restarts: Option<u32>,
+    /// Delay before the first restart of a crash loop
+    pub delay: Duration,
+    /// Multiplier applied to the delay for each further restart
+    pub backoff: f64,
+}
+
+impl RestartPolicy {
+    /// Build the policy configured on a task (`restart: never` if none)
+    pub fn from_task(task: &Task) -> Self {
+        Self {
+            mode: task.restart.unwrap_or_default(),
+            max_restarts: task.max_restarts,
+            delay: task
+                .restart_delay
+                .map(|d| d.as_duration())
+                .unwrap_or(DEFAULT_RESTART_DELAY),
+            backoff: task.restart_backoff.unwrap_or(DEFAULT_RESTART_BACKOFF).max(1.0),
+        }
+    }
+
+    /// Whether to restart after `restarts` earlier restarts, given how the
+    /// process ended
+    pub fn should_restart(&self, restarts: u32, succeeded: bool) -> bool {
+        let wanted = match self.mode {
+            RestartMode::Always => true,
+            RestartMode::OnFailure => !succeeded,
+            RestartMode::Never => false,
+        };
+        wanted && self.max_restarts.map(|max| restarts < max).unwrap_or(true)
+    }
+
+    /// Delay before the `step`-th consecutive restart (1-based)
+    pub fn delay_after(&self, step: u32) -> Duration {
+        backoff_delay(self.delay, self.backoff, step, MAX_RESTART_DELAY)
+    }
+}
+
+/// Restarts of one task during a run of gidterm
+#[derive(Debug, Clone, Default)]
+pub struct RestartHistory {
+    /// Restarts so far
+    pub count: u32,
+    /// Why the process last exited, e.g. "exit code 1"
+    pub last_reason: Option<String>,
+    /// When the process exited, most recent last
+    pub exits: Vec<Instant>,
+    /// Consecutive quick restarts, which drive the backoff
+    step: u32,
+}
+
+impl RestartHistory {
+    /// Record an exit after a run of `uptime`; returns the backoff step
+    /// for the restart that follows
+    pub fn record_exit(&mut self, reason: String, uptime: Option<Duration>) -> u32 {
+        if uptime.is_some_and(|t| t >= RESTART_RESET_AFTER) {
+            self.step = 0;
+        }
+        self.step += 1;
+        self.last_reason = Some(reason);
+        self.exits.push(Instant::now());
+        if self.exits.len() > 20 {
+            self.exits.remove(0);
+        }
+        self.step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(yaml: &str) -> RestartPolicy {
+        let task: Task = serde_yaml::from_str(yaml).unwrap();
+        RestartPolicy::from_task(&task)
+    }
+
+    #[test]
+    fn test_never_restarts_by_default() {
+        let p = policy("description: Build\ncommand: make");
+        assert_eq!(p.mode, RestartMode::Never);
+        assert!(!p.should_restart(0, false));
+        assert!(!p.should_restart(0, true));
+    }
+
+    #[test]
+    fn test_restart_modes_and_limit() {
+        let p = policy("description: Worker\ncommand: ./worker\nrestart: on-failure\nmax_restarts: 2");
+        assert!(p.should_restart(0, false));
+        assert!(!p.should_restart(0, true));
+        assert!(p.should_restart(1, false));
+        assert!(!p.should_restart(2, false));
+
+        let p = policy("description: Dev\ncommand: npm run dev\nrestart: always\nrestart_delay: 500ms");
+        assert!(p.should_restart(100, true));
+        assert_eq!(p.delay_after(1), Duration::from_millis(500));
+        assert_eq!(p.delay_after(3), Duration::from_secs(2));
+        assert_eq!(p.delay_after(100), MAX_RESTART_DELAY);
+    }
+
+    #[test]
+    fn test_long_run_resets_backoff() {
+        let mut history = RestartHistory::default();
+        assert_eq!(history.record_exit("exit code 1".into(), Some(Duration::from_secs(1))), 1);
+        assert_eq!(history.record_exit("exit code 1".into(), Some(Duration::from_secs(1))), 2);
+        assert_eq!(history.record_exit("exit code 2".into(), Some(RESTART_RESET_AFTER)), 1);
+        assert_eq!(history.last_reason.as_deref(), Some("exit code 2"));
+        assert_eq!(history.exits.len(), 3);
+    }
+}
@@ -0,0 +1,118 @@
+//! Retry policies - decide whether and when a failed task runs again
+
+use super::Task;
+use std::time::Duration;
+
+/// Delay before the first retry when `retry_delay` is not set
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Delay multiplier per attempt when `retry_backoff` is not set
+pub const DEFAULT_RETRY_BACKOFF: f64 = 2.0;
+
+/// Upper bound for a single backoff delay
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+
+/// How a task is retried after failing
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt
+    pub retries: u32,
+    /// Delay before the first retry
+    pub delay: Duration,
+    /// Multiplier applied to the delay for each further retry
+    pub backoff: f64,
+    /// Only retry on these exit codes (empty = any failure)
+    pub on_exit_codes: Vec<i32>,
+}
+
+impl RetryPolicy {
+    /// Build the policy configured on a task (`retries: 0` if none)
+    pub fn from_task(task: &Task) -> Self {
+        Self {
+            retries: task.retries.unwrap_or(0),
+            delay: task
+                .retry_delay
+                .map(|d| d.as_duration())
+                .unwrap_or(DEFAULT_RETRY_DELAY),
+            backoff: task.retry_backoff.unwrap_or(DEFAULT_RETRY_BACKOFF).max(1.0),
+            on_exit_codes: task.retry_on_exit_codes.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Total attempts allowed, including the first
+    pub fn max_attempts(&self) -> u32 {
+        self.retries + 1
+    }
+
+    /// Whether a failed `attempt` (1-based) should be retried
+    ///
+    /// `exit_code` is `None` when the task did not exit on its own (e.g. it
+    /// timed out or failed to start); such failures are only retried when no
+    /// exit code filter is set.
+    pub fn should_retry(&self, attempt: u32, exit_code: Option<i32>) -> bool {
+        if attempt >= self.max_attempts() {
+            return false;
+        }
+        if self.on_exit_codes.is_empty() {
+            return true;
+        }
+        exit_code
+            .map(|code| self.on_exit_codes.contains(&code))
+            .unwrap_or(false)
+    }
+
+    /// Delay before running the attempt after the failed `attempt` (1-based)
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        backoff_delay(self.delay, self.backoff, attempt, MAX_RETRY_DELAY)
+    }
+}
+
+/// `base * factor^(step - 1)` for the `step`-th delay (1-based), at most `max`
+pub fn backoff_delay(base: Duration, factor: f64, step: u32, max: Duration) -> Duration {
+    let secs = base.as_secs_f64() * factor.powi(step.saturating_sub(1) as i32);
+    if secs.is_finite() && secs < max.as_secs_f64() {
+        Duration::from_secs_f64(secs)
+    } else {
+        max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(yaml: &str) -> RetryPolicy {
+        let task: Task = serde_yaml::from_str(yaml).unwrap();
+        RetryPolicy::from_task(&task)
+    }
+
+    #[test]
+    fn test_no_retries_by_default() {
+        let p = policy("description: Build\ncommand: make");
+        assert_eq!(p.max_attempts(), 1);
+        assert!(!p.should_retry(1, Some(1)));
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let p = policy("description: Install\ncommand: npm install\nretries: 3\nretry_delay: 2s");
+        assert!(p.should_retry(1, Some(1)));
+        assert!(p.should_retry(3, None));
+        assert!(!p.should_retry(4, Some(1)));
+        assert_eq!(p.delay_after(1), Duration::from_secs(2));
+        assert_eq!(p.delay_after(2), Duration::from_secs(4));
+        assert_eq!(p.delay_after(3), Duration::from_secs(8));
+        assert_eq!(p.delay_after(100), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_retry_on_exit_codes() {
+        let p = policy(
+            "description: Test\ncommand: cargo test\nretries: 2\nretry_backoff: 1\nretry_on_exit_codes: [101]",
+        );
+        assert!(p.should_retry(1, Some(101)));
+        assert!(!p.should_retry(1, Some(1)));
+        assert!(!p.should_retry(1, None));
+        assert_eq!(p.delay_after(2), DEFAULT_RETRY_DELAY);
+    }
+}
@@ -1,53 +1,476 @@
 //! Task Scheduler - DAG-based task dependency scheduling
 
+use super::policy::{PriorityPolicy, SchedulingConfig, SchedulingPolicy};
+use super::writeback::StatusWriter;
 use super::{Graph, GraphTaskStatus};
 use anyhow::Result;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::time::Duration;
 
 /// Task scheduler with dependency resolution
 pub struct Scheduler {
     graph: Graph,
     running: HashSet<String>,
+    status_writer: Option<StatusWriter>,
+    /// Tasks skipped since the last `take_skipped`
+    skipped: Vec<String>,
+    /// Gates that started waiting since the last `take_awaiting_approval`
+    awaiting: Vec<String>,
+    /// Orders the ready tasks
+    policy: Box<dyn SchedulingPolicy>,
+    /// Tasks allowed to run at once (`None` = no limit)
+    max_parallel: Option<NonZeroUsize>,
+    /// Scheduling set on the command line, over the graph's `scheduling:`
+    overrides: SchedulingConfig,
+    /// Average durations of past runs (for `critical-path`)
+    history: HashMap<String, Duration>,
+}
+
+/// What changed when a reloaded graph was applied, task IDs sorted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphDiff {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    /// Changed or deleted in the file, but left as they are while running
+    pub kept_running: Vec<String>,
+    /// The `scheduling:` section changed and is now in effect
+    pub scheduling_changed: bool,
+}
+
+impl GraphDiff {
+    /// True if the reload changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.kept_running.is_empty()
+            && !self.scheduling_changed
+    }
+}
+
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no task changes");
+        }
+        write!(
+            f,
+            "{} added, {} updated, {} removed",
+            self.added.len(),
+            self.updated.len(),
+            self.removed.len()
+        )?;
+        if !self.kept_running.is_empty() {
+            write!(f, ", {} running task(s) unchanged", self.kept_running.len())?;
+        }
+        if self.scheduling_changed {
+            write!(f, ", scheduling changed")?;
+        }
+        Ok(())
+    }
 }
 
 impl Scheduler {
-    /// Create a new scheduler from graph
+    /// Create a new scheduler from graph, using its `scheduling:` section
     pub fn new(graph: Graph) -> Self {
-        Self {
+        let mut scheduler = Self {
             graph,
             running: HashSet::new(),
-        }
+            status_writer: None,
+            skipped: Vec::new(),
+            awaiting: Vec::new(),
+            policy: Box::new(PriorityPolicy),
+            max_parallel: None,
+            overrides: SchedulingConfig::default(),
+            history: HashMap::new(),
+        };
+        scheduler.apply_scheduling();
+        scheduler
+    }
+
+    /// Use the graph's `scheduling:` with `overrides` (command-line flags)
+    /// on top; `history` holds average durations of past runs (for
+    /// `critical-path`)
+    ///
+    /// Both are kept, so a `scheduling:` changed by a reload is applied
+    /// under the same overrides.
+    pub fn configure(&mut self, overrides: &SchedulingConfig, history: &HashMap<String, Duration>) {
+        self.overrides = *overrides;
+        self.history = history.clone();
+        self.apply_scheduling();
+    }
+
+    /// Scheduling in effect: the overrides, then the graph's `scheduling:`
+    pub fn scheduling(&self) -> SchedulingConfig {
+        self.overrides.or(self.graph.scheduling.unwrap_or_default())
+    }
+
+    fn apply_scheduling(&mut self) {
+        let config = self.scheduling();
+        self.policy = config.policy.unwrap_or_default().build(&self.history);
+        self.max_parallel = config.max_parallel;
+    }
+
+    /// Use a custom scheduling policy
+    pub fn set_policy(&mut self, policy: Box<dyn SchedulingPolicy>) {
+        self.policy = policy;
+    }
+
+    /// Name of the scheduling policy in use
+    pub fn policy_name(&self) -> &'static str {
+        self.policy.name()
+    }
+
+    /// Enable write-back of status changes to the source graph file(s)
+    pub fn set_status_writer(&mut self, writer: StatusWriter) {
+        self.status_writer = Some(writer);
     }
 
-    /// Schedule next tasks to run
+    /// Schedule next tasks to run, in the order the policy picks
+    ///
+    /// Gates whose dependencies are met are not returned: they move to
+    /// `AwaitingApproval` until `approve` or `reject` is called. With a
+    /// parallelism limit, only as many tasks as there are free slots are
+    /// returned.
     pub fn schedule_next(&mut self) -> Vec<String> {
+        self.propagate_skips();
         let ready = self.graph.get_ready_tasks();
-        
+
+        let (mut gates, ready): (Vec<String>, Vec<String>) = ready
+            .into_iter()
+            .partition(|id| self.graph.get_task(id).is_some_and(|t| t.is_gate()));
+        gates.sort();
+        for gate in gates {
+            log::debug!("Gate {} is awaiting approval", gate);
+            if let Err(e) = self.set_status(&gate, GraphTaskStatus::AwaitingApproval) {
+                log::warn!("Failed to hold gate {}: {}", gate, e);
+                continue;
+            }
+            self.awaiting.push(gate);
+        }
+
         // Filter out tasks that are already running
-        ready
+        let mut ready: Vec<String> = ready
             .into_iter()
             .filter(|id| !self.running.contains(id))
-            .collect()
+            .collect();
+
+        // Ready services keep running but no longer hold a slot
+        let slots = self.max_parallel.map(|max| {
+            let busy = self
+                .running
+                .iter()
+                .filter(|id| self.graph.get_task(id).is_some_and(|t| t.status == GraphTaskStatus::InProgress))
+                .count();
+            max.get().saturating_sub(busy)
+        });
+        if ready.is_empty() || slots == Some(0) {
+            return Vec::new();
+        }
+        self.policy.order(&self.graph, &mut ready);
+        if let Some(slots) = slots {
+            ready.truncate(slots);
+        }
+        ready
+    }
+
+    /// Approve a gate, releasing its dependents
+    pub fn approve(&mut self, task_id: &str) -> Result<()> {
+        self.ensure_awaiting_approval(task_id)?;
+        self.set_status(task_id, GraphTaskStatus::Done)?;
+        self.propagate_skips();
+        Ok(())
+    }
+
+    /// Reject a gate; it counts as failed for its dependents
+    pub fn reject(&mut self, task_id: &str) -> Result<()> {
+        self.ensure_awaiting_approval(task_id)?;
+        self.set_status(task_id, GraphTaskStatus::Failed)?;
+        self.propagate_skips();
+        Ok(())
+    }
+
+    fn ensure_awaiting_approval(&self, task_id: &str) -> Result<()> {
+        match self.graph.get_task(task_id) {
+            None => anyhow::bail!("Task {} not found", task_id),
+            Some(task) if task.status != GraphTaskStatus::AwaitingApproval => {
+                anyhow::bail!("Task {} is not awaiting approval ({})", task_id, task.status)
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Gates currently awaiting approval, sorted
+    pub fn awaiting_approval(&self) -> Vec<String> {
+        let mut gates: Vec<String> = self
+            .graph
+            .all_tasks()
+            .iter()
+            .filter(|(_, t)| t.status == GraphTaskStatus::AwaitingApproval)
+            .map(|(id, _)| id.clone())
+            .collect();
+        gates.sort();
+        gates
     }
 
     /// Mark task as started
     pub fn mark_started(&mut self, task_id: &str) -> Result<()> {
-        self.graph.update_task_status(task_id, GraphTaskStatus::InProgress)?;
+        self.set_status(task_id, GraphTaskStatus::InProgress)?;
         self.running.insert(task_id.to_string());
         Ok(())
     }
 
     /// Mark task as completed
     pub fn mark_done(&mut self, task_id: &str) -> Result<()> {
-        self.graph.update_task_status(task_id, GraphTaskStatus::Done)?;
+        self.set_status(task_id, GraphTaskStatus::Done)?;
         self.running.remove(task_id);
+        self.propagate_skips();
         Ok(())
     }
 
-    /// Mark task as failed
+    /// Mark task as failed, skipping dependents that can no longer run
     pub fn mark_failed(&mut self, task_id: &str) -> Result<()> {
-        self.graph.update_task_status(task_id, GraphTaskStatus::Failed)?;
+        self.set_status(task_id, GraphTaskStatus::Failed)?;
         self.running.remove(task_id);
+        self.propagate_skips();
+        Ok(())
+    }
+
+    /// Mark a running service as ready, releasing its dependents
+    pub fn mark_ready(&mut self, task_id: &str) -> Result<()> {
+        self.set_status(task_id, GraphTaskStatus::Ready)
+    }
+
+    /// Mark task as up to date without running it
+    pub fn mark_cached(&mut self, task_id: &str) -> Result<()> {
+        self.set_status(task_id, GraphTaskStatus::Cached)?;
+        self.propagate_skips();
+        Ok(())
+    }
+
+    /// Reset tasks that are running or awaiting approval to pending, when
+    /// gidterm stops before they finish; returns their IDs, sorted
+    ///
+    /// With write-back enabled the graph file says `pending` again, so the
+    /// next run starts them. The caller stops the processes.
+    pub fn interrupt(&mut self) -> Vec<String> {
+        let mut interrupted: Vec<String> = self
+            .graph
+            .all_tasks()
+            .iter()
+            .filter(|(_, task)| task.status.is_unfinished())
+            .map(|(id, _)| id.clone())
+            .collect();
+        interrupted.sort();
+        for task_id in &interrupted {
+            if let Err(e) = self.set_status(task_id, GraphTaskStatus::Pending) {
+                log::warn!("Failed to reset task {}: {}", task_id, e);
+            }
+        }
+        self.running.clear();
+        self.awaiting.clear();
+        interrupted
+    }
+
+    /// Set a task's status from a previous session, without running it
+    ///
+    /// Dependents that will not run after it (e.g. `on: failure` tasks of a
+    /// task that succeeded) are skipped.
+    pub fn restore(&mut self, task_id: &str, status: GraphTaskStatus) -> Result<()> {
+        self.set_status(task_id, status)?;
+        self.running.remove(task_id);
+        self.propagate_skips();
+        Ok(())
+    }
+
+    /// Reset a task and everything downstream of it to pending, so they run
+    /// again
+    ///
+    /// Only tasks that already started or finished are reset; tasks still
+    /// waiting are left alone. Returns the reset task IDs, sorted. Any that
+    /// are still running must be stopped by the caller.
+    pub fn requeue(&mut self, task_id: &str) -> Result<Vec<String>> {
+        if self.graph.get_task(task_id).is_none() {
+            anyhow::bail!("Task {} not found", task_id);
+        }
+
+        let mut affected = BTreeSet::from([task_id.to_string()]);
+        loop {
+            let dependents: Vec<String> = self
+                .graph
+                .all_tasks()
+                .iter()
+                .filter(|(id, task)| {
+                    !affected.contains(*id)
+                        && task.depends_on.iter().flatten().any(|dep| affected.contains(dep))
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            if dependents.is_empty() {
+                break;
+            }
+            affected.extend(dependents);
+        }
+
+        let mut requeued = Vec::new();
+        for id in affected {
+            let status = &self.graph.get_task(&id).unwrap().status;
+            if matches!(status, GraphTaskStatus::Pending | GraphTaskStatus::Planned) {
+                continue;
+            }
+            self.set_status(&id, GraphTaskStatus::Pending)?;
+            self.running.remove(&id);
+            self.skipped.retain(|s| *s != id);
+            self.awaiting.retain(|s| *s != id);
+            requeued.push(id);
+        }
+        Ok(requeued)
+    }
+
+    /// Apply a reloaded graph without disturbing running tasks
+    ///
+    /// New tasks are added, tasks that are not running get their new
+    /// definition (keeping their status), and deleted tasks that are not
+    /// running are removed. Running tasks stay as they are.
+    pub fn apply_graph(&mut self, graph: Graph) -> GraphDiff {
+        let mut diff = GraphDiff::default();
+        let Graph {
+            include,
+            vars,
+            metadata,
+            scheduling,
+            nodes,
+            tasks,
+        } = graph;
+
+        let deleted: Vec<String> = self
+            .graph
+            .tasks
+            .keys()
+            .filter(|id| !tasks.contains_key(*id))
+            .cloned()
+            .collect();
+        for id in deleted {
+            if self.is_active(&id) {
+                diff.kept_running.push(id);
+            } else {
+                self.graph.tasks.remove(&id);
+                self.skipped.retain(|s| *s != id);
+                diff.removed.push(id);
+            }
+        }
+
+        for (id, mut task) in tasks {
+            let Some(current) = self.graph.tasks.get(&id) else {
+                self.graph.tasks.insert(id.clone(), task);
+                diff.added.push(id);
+                continue;
+            };
+            // The file's status is not the live one
+            task.status = current.status.clone();
+            if task == *current {
+                continue;
+            }
+            if self.is_active(&id) {
+                diff.kept_running.push(id);
+            } else {
+                self.graph.tasks.insert(id.clone(), task);
+                diff.updated.push(id);
+            }
+        }
+
+        self.graph.include = include;
+        self.graph.vars = vars;
+        self.graph.metadata = metadata;
+        if scheduling != self.graph.scheduling {
+            self.graph.scheduling = scheduling;
+            self.apply_scheduling();
+            diff.scheduling_changed = true;
+        }
+        self.graph.nodes = nodes;
+        self.propagate_skips();
+
+        for list in [&mut diff.added, &mut diff.updated, &mut diff.removed, &mut diff.kept_running] {
+            list.sort();
+        }
+        diff
+    }
+
+    /// True if the task is running (or waiting to be retried or restarted)
+    fn is_active(&self, task_id: &str) -> bool {
+        self.running.contains(task_id)
+            || self.graph.get_task(task_id).is_some_and(|t| {
+                matches!(t.status, GraphTaskStatus::InProgress | GraphTaskStatus::Ready)
+            })
+    }
+
+    /// Tasks marked skipped since the last call
+    pub fn take_skipped(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.skipped)
+    }
+
+    /// Gates that started awaiting approval since the last call
+    pub fn take_awaiting_approval(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.awaiting)
+    }
+
+    /// Mark pending tasks whose `on` condition can no longer be met as skipped
+    ///
+    /// Repeats until nothing changes, so skips cascade down the graph.
+    fn propagate_skips(&mut self) {
+        loop {
+            let mut to_skip: Vec<String> = self
+                .graph
+                .all_tasks()
+                .keys()
+                .filter(|id| self.graph.should_skip(id))
+                .cloned()
+                .collect();
+            if to_skip.is_empty() {
+                break;
+            }
+            to_skip.sort();
+
+            for task_id in to_skip {
+                log::debug!("Skipping task {}", task_id);
+                if let Err(e) = self.set_status(&task_id, GraphTaskStatus::Skipped) {
+                    log::warn!("Failed to skip task {}: {}", task_id, e);
+                }
+                self.skipped.push(task_id);
+            }
+        }
+    }
+
+    /// Update status in the graph and write it back if enabled
+    fn set_status(&mut self, task_id: &str, status: GraphTaskStatus) -> Result<()> {
+        self.graph.update_task_status(task_id, status.clone())?;
+
+        // Matrix variants only exist after expansion, and included files
+        // are shared fragments: neither is written back
+        let in_source_file = self
+            .graph
+            .get_task(task_id)
+            .map(|t| t.matrix_group.is_none() && t.included_from.is_none())
+            .unwrap_or(false);
+        if !in_source_file {
+            return Ok(());
+        }
+
+        // A cached task is as good as done in the source file, a ready
+        // service is still in progress and a held gate is still pending
+        let status = match status {
+            GraphTaskStatus::Cached => GraphTaskStatus::Done,
+            GraphTaskStatus::Ready | GraphTaskStatus::AwaitingApproval => return Ok(()),
+            status => status,
+        };
+        if let Some(writer) = &self.status_writer {
+            if let Err(e) = writer.write(task_id, &status) {
+                log::warn!("Failed to write status of {} back to graph: {}", task_id, e);
+            }
+        }
         Ok(())
     }
 
@@ -68,6 +491,14 @@ impl Scheduler {
                 .graph
                 .all_tasks()
                 .values()
-                .all(|task| task.status == GraphTaskStatus::Done || task.status == GraphTaskStatus::Failed)
+                .all(|task| {
+                    matches!(
+                        task.status,
+                        GraphTaskStatus::Done
+                            | GraphTaskStatus::Failed
+                            | GraphTaskStatus::Skipped
+                            | GraphTaskStatus::Cached
+                    )
+                })
     }
 }
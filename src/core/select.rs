@@ -0,0 +1,198 @@
+//! Target selection - run some tasks and only what they depend on
+//!
+//! ```text
+//! gidterm run test                       # test and its transitive dependencies
+//! gidterm run --tags smoke,unit          # tasks tagged smoke or unit (+ deps)
+//! gidterm run --component api            # tasks of the api component (+ deps)
+//! gidterm run e2e --exclude install      # install is assumed done
+//! ```
+//!
+//! A target names a task ID or a matrix task (every variant). In workspace
+//! mode an unqualified name selects that task in every project that has it.
+
+use super::Graph;
+use anyhow::Result;
+use std::collections::{BTreeSet, HashSet};
+
+/// Which tasks of a graph to run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskSelection {
+    /// Tasks to run (all tasks if empty)
+    pub targets: Vec<String>,
+    /// Keep only targets with at least one of these tags
+    pub tags: Vec<String>,
+    /// Keep only targets in one of these components
+    pub components: Vec<String>,
+    /// Tasks to leave out; dependents treat them as satisfied
+    pub exclude: Vec<String>,
+}
+
+impl TaskSelection {
+    /// True if the selection keeps every task
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+            && self.tags.is_empty()
+            && self.components.is_empty()
+            && self.exclude.is_empty()
+    }
+
+    /// IDs of the selected tasks, sorted: the filtered targets and their
+    /// transitive dependencies, minus the excluded tasks
+    pub fn resolve(&self, graph: &Graph) -> Result<BTreeSet<String>> {
+        let mut roots = BTreeSet::new();
+        if self.targets.is_empty() {
+            roots.extend(graph.tasks.keys().cloned());
+        } else {
+            for target in &self.targets {
+                roots.extend(matching(graph, target)?);
+            }
+        }
+
+        roots.retain(|id| {
+            let task = &graph.tasks[id];
+            let tagged = self.tags.is_empty()
+                || task.tags.iter().flatten().any(|tag| self.tags.contains(tag));
+            let in_component = self.components.is_empty()
+                || task.component.as_ref().is_some_and(|c| self.components.contains(c));
+            tagged && in_component
+        });
+
+        let mut excluded = HashSet::new();
+        for pattern in &self.exclude {
+            excluded.extend(matching(graph, pattern)?);
+        }
+
+        if roots.iter().all(|id| excluded.contains(id)) {
+            anyhow::bail!("No tasks match the selection");
+        }
+
+        // Dependency closure; excluded tasks are not followed
+        let mut selected = BTreeSet::new();
+        let mut stack: Vec<String> = roots.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if excluded.contains(&id) || !selected.insert(id.clone()) {
+                continue;
+            }
+            if let Some(task) = graph.tasks.get(&id) {
+                stack.extend(task.depends_on.iter().flatten().cloned());
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Copy of the graph with only the selected tasks
+    ///
+    /// Dependencies on excluded tasks are dropped, so their dependents run
+    /// as if they had succeeded.
+    pub fn apply(&self, graph: &Graph) -> Result<Graph> {
+        let selected = self.resolve(graph)?;
+        let mut selection = graph.clone();
+        selection.tasks.retain(|id, _| selected.contains(id));
+        for task in selection.tasks.values_mut() {
+            if let Some(deps) = &mut task.depends_on {
+                // Unknown dependencies stay, so validation still reports them
+                deps.retain(|dep| selected.contains(dep) || !graph.tasks.contains_key(dep));
+            }
+        }
+        Ok(selection)
+    }
+}
+
+/// Tasks a target or exclude name refers to: the task itself, the variants
+/// of a matrix task, or the task in every project (workspace mode)
+fn matching(graph: &Graph, name: &str) -> Result<BTreeSet<String>> {
+    if graph.tasks.contains_key(name) {
+        return Ok(BTreeSet::from([name.to_string()]));
+    }
+    let variants: BTreeSet<String> = graph
+        .tasks
+        .iter()
+        .filter(|(_, task)| task.matrix_group.as_deref() == Some(name))
+        .map(|(id, _)| id.clone())
+        .collect();
+    if !variants.is_empty() {
+        return Ok(variants);
+    }
+
+    let suffix = format!(":{}", name);
+    let in_projects: BTreeSet<String> = graph
+        .tasks
+        .iter()
+        .filter(|(id, task)| {
+            id.ends_with(&suffix)
+                || task.matrix_group.as_deref().is_some_and(|group| group.ends_with(&suffix))
+        })
+        .map(|(id, _)| id.clone())
+        .collect();
+    if in_projects.is_empty() {
+        anyhow::bail!("Unknown task '{}'", name);
+    }
+    Ok(in_projects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        serde_yaml::from_str(
+            r#"
+tasks:
+  install: { description: Install, command: npm ci }
+  build: { description: Build, command: npm run build, depends_on: [install], component: web }
+  test: { description: Test, command: npm test, depends_on: [build], tags: [unit] }
+  lint: { description: Lint, command: npm run lint, depends_on: [install], tags: [unit, style] }
+  deploy: { description: Deploy, command: ./deploy, depends_on: [test] }
+"#,
+        )
+        .unwrap()
+    }
+
+    fn ids(selection: &TaskSelection) -> Vec<String> {
+        selection.resolve(&graph()).unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn test_targets_and_filters() {
+        let targets = |t: &[&str]| TaskSelection {
+            targets: t.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+        assert_eq!(ids(&targets(&["test"])), vec!["build", "install", "test"]);
+        assert_eq!(ids(&TaskSelection::default()).len(), 5);
+        assert!(targets(&["missing"]).resolve(&graph()).is_err());
+
+        let tagged = TaskSelection {
+            tags: vec!["style".into()],
+            ..Default::default()
+        };
+        assert_eq!(ids(&tagged), vec!["install", "lint"]);
+
+        let component = TaskSelection {
+            targets: vec!["deploy".into(), "lint".into()],
+            components: vec!["web".into()],
+            ..Default::default()
+        };
+        assert!(component.resolve(&graph()).is_err());
+        let component = TaskSelection {
+            components: vec!["web".into()],
+            ..Default::default()
+        };
+        assert_eq!(ids(&component), vec!["build", "install"]);
+    }
+
+    #[test]
+    fn test_exclude_drops_dependency() {
+        let selection = TaskSelection {
+            targets: vec!["test".into()],
+            exclude: vec!["install".into()],
+            ..Default::default()
+        };
+        let graph = selection.apply(&graph()).unwrap();
+        let mut ids: Vec<&String> = graph.tasks.keys().collect();
+        ids.sort();
+        assert_eq!(ids, vec!["build", "test"]);
+        assert_eq!(graph.tasks["build"].depends_on, Some(vec![]));
+        assert_eq!(graph.get_ready_tasks(), vec!["build"]);
+    }
+}
@@ -0,0 +1,566 @@
+//! Graph validation - detect cycles, dangling dependencies, ID collisions
+//! and unresolved `${...}` variables
+//!
+//! Runs automatically when a graph is loaded from disk and backs the
+//! `gidterm validate` subcommand.
+
+use super::interpolate::{self, Reference};
+use super::policy::PolicyKind;
+use super::probe::ReadinessProbe;
+use super::graph::split_project_ref;
+use super::Graph;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// How serious a validation issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueSeverity {
+    /// Graph can still run, but something looks off
+    Warning,
+    /// Graph cannot run correctly
+    Error,
+}
+
+impl fmt::Display for IssueSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found in a graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// Tasks depend on each other in a loop (path ends where it started)
+    Cycle { path: Vec<String> },
+    /// A task depends on an ID that is not a task
+    UnknownDependency { task: String, dependency: String },
+    /// A task depends on a task in another project (`backend:build`), which
+    /// only resolves in workspace mode
+    ExternalDependency { task: String, dependency: String },
+    /// A task without a command is depended upon by other tasks
+    MissingCommand { task: String, dependents: Vec<String> },
+    /// The same ID is used for both a node and a task
+    IdCollision { id: String },
+    /// A task refers to a variable gidterm cannot resolve
+    UnresolvedVariable { task: String, variable: String },
+    /// A task refers to an environment variable that is not set
+    UnsetEnvVariable { task: String, variable: String },
+    /// A task uses an output of a task it does not (transitively) depend on
+    OutputNotUpstream { task: String, variable: String, source: String },
+    /// An `outputs_capture` pattern is not a valid regex
+    InvalidCapture { task: String, key: String, error: String },
+    /// A service's `ready_when` cannot be used
+    InvalidProbe { task: String, error: String },
+    /// `ready_when` on a task that is not `kind: service`
+    ProbeWithoutService { task: String },
+    /// A `type: gate` task has a command, which never runs
+    GateWithCommand { task: String },
+    /// Workspace projects ask for different scheduling policies (project, policy)
+    ConflictingPolicies { policies: Vec<(String, PolicyKind)> },
+}
+
+impl ValidationIssue {
+    /// Get severity of this issue
+    pub fn severity(&self) -> IssueSeverity {
+        match self {
+            Self::Cycle { .. }
+            | Self::UnknownDependency { .. }
+            | Self::UnresolvedVariable { .. }
+            | Self::OutputNotUpstream { .. }
+            | Self::InvalidCapture { .. }
+            | Self::InvalidProbe { .. } => IssueSeverity::Error,
+            Self::MissingCommand { .. }
+            | Self::IdCollision { .. }
+            | Self::ExternalDependency { .. }
+            | Self::UnsetEnvVariable { .. }
+            | Self::ProbeWithoutService { .. }
+            | Self::GateWithCommand { .. }
+            | Self::ConflictingPolicies { .. } => IssueSeverity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle { path } => write!(f, "dependency cycle: {}", path.join(" -> ")),
+            Self::UnknownDependency { task, dependency } => {
+                write!(f, "task '{}' depends on unknown task '{}'", task, dependency)
+            }
+            Self::ExternalDependency { task, dependency } => write!(
+                f,
+                "task '{}' depends on '{}' in another project (only resolved with --workspace)",
+                task, dependency
+            ),
+            Self::MissingCommand { task, dependents } => write!(
+                f,
+                "task '{}' has no command but is depended on by: {}",
+                task,
+                dependents.join(", ")
+            ),
+            Self::IdCollision { id } => write!(f, "'{}' is defined as both a node and a task", id),
+            Self::UnresolvedVariable { task, variable } => {
+                write!(f, "task '{}' uses unresolved variable {}", task, variable)
+            }
+            Self::UnsetEnvVariable { task, variable } => write!(
+                f,
+                "task '{}' uses {}, which is not set in the environment",
+                task, variable
+            ),
+            Self::OutputNotUpstream { task, variable, source } => write!(
+                f,
+                "task '{}' uses {} but does not depend on '{}'",
+                task, variable, source
+            ),
+            Self::InvalidCapture { task, key, error } => {
+                write!(f, "task '{}' has an invalid outputs_capture pattern for '{}': {}", task, key, error)
+            }
+            Self::InvalidProbe { task, error } => write!(f, "service '{}': {}", task, error),
+            Self::ProbeWithoutService { task } => write!(
+                f,
+                "task '{}' has ready_when but is not a service (add kind: service)",
+                task
+            ),
+            Self::GateWithCommand { task } => write!(
+                f,
+                "gate '{}' has a command, which is never run (gates only wait for approval)",
+                task
+            ),
+            Self::ConflictingPolicies { policies } => {
+                let policies: Vec<String> =
+                    policies.iter().map(|(project, policy)| format!("{}: {}", project, policy)).collect();
+                write!(
+                    f,
+                    "projects use different scheduling policies ({}); using priority (pick one with --policy)",
+                    policies.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Result of validating a graph
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Issues that prevent the graph from running
+    pub fn errors(&self) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity() == IssueSeverity::Error)
+            .collect()
+    }
+
+    /// Issues that are reported but do not block execution
+    pub fn warnings(&self) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity() == IssueSeverity::Warning)
+            .collect()
+    }
+
+    /// True if there are no errors (warnings are allowed)
+    pub fn is_ok(&self) -> bool {
+        self.errors().is_empty()
+    }
+}
+
+/// Validate a graph and collect every issue found
+pub fn validate(graph: &Graph) -> ValidationReport {
+    let mut issues = Vec::new();
+
+    let mut task_ids: Vec<&String> = graph.tasks.keys().collect();
+    task_ids.sort();
+
+    // Unknown dependencies
+    for id in &task_ids {
+        let task = &graph.tasks[*id];
+        for dep in task.depends_on.iter().flatten() {
+            if graph.tasks.contains_key(dep) {
+                continue;
+            }
+            let task = (*id).clone();
+            let dependency = dep.clone();
+            issues.push(if split_project_ref(dep).is_some() {
+                ValidationIssue::ExternalDependency { task, dependency }
+            } else {
+                ValidationIssue::UnknownDependency { task, dependency }
+            });
+        }
+    }
+
+    // Cycles
+    for path in find_cycles(graph, &task_ids) {
+        issues.push(ValidationIssue::Cycle { path });
+    }
+
+    // Command-less tasks that others wait on (gates wait for approval instead)
+    let mut dependents: HashMap<&str, Vec<String>> = HashMap::new();
+    for id in &task_ids {
+        for dep in graph.tasks[*id].depends_on.iter().flatten() {
+            dependents.entry(dep.as_str()).or_default().push((*id).clone());
+        }
+    }
+    for id in &task_ids {
+        let task = &graph.tasks[*id];
+        let has_command = task
+            .command
+            .as_ref()
+            .map(|c| !c.trim().is_empty())
+            .unwrap_or(false);
+        if task.is_gate() {
+            if has_command {
+                issues.push(ValidationIssue::GateWithCommand { task: (*id).clone() });
+            }
+            continue;
+        }
+        if has_command {
+            continue;
+        }
+        if let Some(deps) = dependents.remove(id.as_str()) {
+            issues.push(ValidationIssue::MissingCommand {
+                task: (*id).clone(),
+                dependents: deps,
+            });
+        }
+    }
+
+    // Node/task ID collisions
+    for id in &task_ids {
+        if graph.nodes.contains_key(*id) {
+            issues.push(ValidationIssue::IdCollision { id: (*id).clone() });
+        }
+    }
+
+    // Variables that cannot be resolved when the task starts
+    for id in &task_ids {
+        let task = &graph.tasks[*id];
+        let mut seen = Vec::new();
+        for reference in task.variable_fields().iter().flat_map(|text| interpolate::references(text)) {
+            if seen.contains(&reference) {
+                continue;
+            }
+            seen.push(reference.clone());
+
+            let variable = reference.to_string();
+            let Reference { namespace, field } = reference;
+            match (namespace.as_str(), field.as_deref()) {
+                ("vars", Some(name))
+                    if graph.vars.as_ref().is_some_and(|v| v.contains_key(name)) => {}
+                ("env", Some(name)) => {
+                    let in_task = task.env.as_ref().is_some_and(|e| e.contains_key(name));
+                    if !in_task && std::env::var_os(name).is_none() {
+                        issues.push(ValidationIssue::UnsetEnvVariable {
+                            task: (*id).clone(),
+                            variable,
+                        });
+                    }
+                }
+                ("project", Some("root" | "name")) | ("task", Some("id")) | ("port", None) => {}
+                ("tasks", Some(field)) => match interpolate::output_reference(field) {
+                    Some((source, _)) if graph.tasks.contains_key(source) => {
+                        if !upstream(graph, id).contains(source) {
+                            issues.push(ValidationIssue::OutputNotUpstream {
+                                task: (*id).clone(),
+                                variable,
+                                source: source.to_string(),
+                            });
+                        }
+                    }
+                    _ => issues.push(ValidationIssue::UnresolvedVariable {
+                        task: (*id).clone(),
+                        variable,
+                    }),
+                },
+                _ => issues.push(ValidationIssue::UnresolvedVariable {
+                    task: (*id).clone(),
+                    variable,
+                }),
+            }
+        }
+    }
+
+    // Capture patterns that will not compile
+    for id in &task_ids {
+        let mut captures: Vec<_> = graph.tasks[*id].outputs_capture.iter().flatten().collect();
+        captures.sort();
+        for (key, pattern) in captures {
+            if let Err(e) = Regex::new(pattern) {
+                issues.push(ValidationIssue::InvalidCapture {
+                    task: (*id).clone(),
+                    key: key.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    // Readiness probes
+    for id in &task_ids {
+        let task = &graph.tasks[*id];
+        let Some(ready_when) = &task.ready_when else {
+            continue;
+        };
+        if !task.is_service() {
+            issues.push(ValidationIssue::ProbeWithoutService { task: (*id).clone() });
+        } else if let Err(e) = ReadinessProbe::new(ready_when, None) {
+            issues.push(ValidationIssue::InvalidProbe {
+                task: (*id).clone(),
+                error: e.to_string(),
+            });
+        }
+    }
+
+    ValidationReport { issues }
+}
+
+/// Every task a task depends on, directly or transitively
+fn upstream(graph: &Graph, id: &str) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut stack: Vec<&String> = graph
+        .tasks
+        .get(id)
+        .and_then(|t| t.depends_on.as_ref())
+        .map(|d| d.iter().collect())
+        .unwrap_or_default();
+    while let Some(dep) = stack.pop() {
+        if seen.insert(dep.clone()) {
+            if let Some(deps) = graph.tasks.get(dep).and_then(|t| t.depends_on.as_ref()) {
+                stack.extend(deps);
+            }
+        }
+    }
+    seen
+}
+
+/// DFS visit state for cycle detection
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Visiting,
+    Visited,
+}
+
+/// Find dependency cycles, each reported once as a closed path
+fn find_cycles(graph: &Graph, task_ids: &[&String]) -> Vec<Vec<String>> {
+    let mut marks: HashMap<String, Mark> = HashMap::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut cycles = Vec::new();
+
+    for id in task_ids {
+        if !marks.contains_key(id.as_str()) {
+            visit(graph, id, &mut marks, &mut stack, &mut cycles);
+        }
+    }
+
+    cycles
+}
+
+fn visit(
+    graph: &Graph,
+    id: &str,
+    marks: &mut HashMap<String, Mark>,
+    stack: &mut Vec<String>,
+    cycles: &mut Vec<Vec<String>>,
+) {
+    marks.insert(id.to_string(), Mark::Visiting);
+    stack.push(id.to_string());
+
+    let mut deps: Vec<&String> = graph
+        .tasks
+        .get(id)
+        .and_then(|t| t.depends_on.as_ref())
+        .map(|d| d.iter().collect())
+        .unwrap_or_default();
+    deps.sort();
+
+    for dep in deps {
+        if !graph.tasks.contains_key(dep) {
+            continue; // Reported as an unknown dependency
+        }
+        match marks.get(dep.as_str()) {
+            Some(Mark::Visiting) => {
+                let start = stack.iter().position(|s| s == dep).unwrap_or(0);
+                let mut path = stack[start..].to_vec();
+                path.push(dep.clone());
+                cycles.push(path);
+            }
+            Some(Mark::Visited) => {}
+            None => visit(graph, dep, marks, stack, cycles),
+        }
+    }
+
+    stack.pop();
+    marks.insert(id.to_string(), Mark::Visited);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(yaml: &str) -> Graph {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_valid_graph() {
+        let g = graph(
+            r#"
+tasks:
+  build: { description: Build, command: cargo build }
+  test: { description: Test, command: cargo test, depends_on: [build] }
+"#,
+        );
+        let report = validate(&g);
+        assert!(report.is_ok());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let g = graph(
+            r#"
+tasks:
+  a: { description: A, command: "true", depends_on: [c] }
+  b: { description: B, command: "true", depends_on: [a] }
+  c: { description: C, command: "true", depends_on: [b] }
+"#,
+        );
+        let report = validate(&g);
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.issues,
+            vec![ValidationIssue::Cycle {
+                path: vec!["a".into(), "c".into(), "b".into(), "a".into()]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cross_project_dependency_is_a_warning() {
+        let g = graph(
+            r#"
+tasks:
+  "docker:build": { description: Image, command: docker build . }
+  e2e: { description: E2E, command: npm run e2e, depends_on: ["docker:build", "backend:serve", "@shared/build"] }
+"#,
+        );
+        let report = validate(&g);
+        assert!(report.is_ok());
+        assert_eq!(
+            report.warnings(),
+            vec![
+                &ValidationIssue::ExternalDependency {
+                    task: "e2e".into(),
+                    dependency: "backend:serve".into()
+                },
+                &ValidationIssue::ExternalDependency {
+                    task: "e2e".into(),
+                    dependency: "@shared/build".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_dependency_and_warnings() {
+        let g = graph(
+            r#"
+nodes:
+  build: { type: Feature, description: Build feature, status: active }
+tasks:
+  build: { description: Build, depends_on: [fetch] }
+  test: { description: Test, command: cargo test, depends_on: [build] }
+"#,
+        );
+        let report = validate(&g);
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(
+            report.errors()[0],
+            &ValidationIssue::UnknownDependency {
+                task: "build".into(),
+                dependency: "fetch".into()
+            }
+        );
+        assert_eq!(report.warnings().len(), 2);
+        assert!(report.issues.contains(&ValidationIssue::MissingCommand {
+            task: "build".into(),
+            dependents: vec!["test".into()]
+        }));
+        assert!(report
+            .issues
+            .contains(&ValidationIssue::IdCollision { id: "build".into() }));
+    }
+
+    #[test]
+    fn test_output_references() {
+        let g = graph(
+            r#"
+tasks:
+  build: { description: Build, command: make, outputs_capture: { tag: "tagged (\\S+)", bad: "(" } }
+  push: { description: Push, command: "push ${tasks.build.outputs.tag}", depends_on: [build] }
+  deploy: { description: Deploy, command: "deploy ${tasks.build.outputs.tag}", depends_on: [push] }
+  docs: { description: Docs, command: "docs ${tasks.build.outputs.tag} ${tasks.nope.outputs.x}" }
+"#,
+        );
+        let report = validate(&g);
+        let errors: Vec<String> = report.errors().iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(report.issues.contains(&ValidationIssue::OutputNotUpstream {
+            task: "docs".into(),
+            variable: "${tasks.build.outputs.tag}".into(),
+            source: "build".into()
+        }));
+        assert!(report.issues.contains(&ValidationIssue::UnresolvedVariable {
+            task: "docs".into(),
+            variable: "${tasks.nope.outputs.x}".into()
+        }));
+        assert!(matches!(
+            report.errors()[2],
+            ValidationIssue::InvalidCapture { key, .. } if key == "bad"
+        ));
+    }
+
+    #[test]
+    fn test_unresolved_variables() {
+        let g = graph(
+            r#"
+vars:
+  model: resnet50
+tasks:
+  train:
+    description: Train
+    command: "python train.py ${vars.model} ${vars.dataset} --port ${port} --out ${HOME}"
+    env: { RUN: "${task.id}", KEY: "${env.GIDTERM_TEST_UNSET_VARIABLE}" }
+  eval: { description: Eval, command: "eval ${project.dir}" }
+"#,
+        );
+        let report = validate(&g);
+        assert_eq!(
+            report.errors(),
+            vec![
+                &ValidationIssue::UnresolvedVariable {
+                    task: "eval".into(),
+                    variable: "${project.dir}".into()
+                },
+                &ValidationIssue::UnresolvedVariable {
+                    task: "train".into(),
+                    variable: "${vars.dataset}".into()
+                },
+            ]
+        );
+        assert_eq!(
+            report.warnings(),
+            vec![&ValidationIssue::UnsetEnvVariable {
+                task: "train".into(),
+                variable: "${env.GIDTERM_TEST_UNSET_VARIABLE}".into()
+            }]
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! Watch mode - re-run tasks when the files they watch change
+//!
+//! ```yaml
+//! test:
+//!   command: cargo test
+//!   watch: [src/**/*.rs, tests/]
+//! ```
+//!
+//! With `gidterm run --watch` the watched files are polled. Once changes
+//! have settled for the debounce period, the task runs again along with its
+//! dependents. Files ignored by `.gitignore` never trigger a re-run.
+
+use super::cache;
+use super::Task;
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Time between scans of the watched files
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Quiet period after the last change before tasks re-run
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Directories that are never watched (gidterm writes its sessions and cache
+/// to `.gidterm`)
+const ALWAYS_IGNORED: &[&str] = &[".git", ".gidterm"];
+
+/// Modification time and size of each watched file
+type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// The watched files of one task (or the graph files)
+#[derive(Debug)]
+struct WatchSet {
+    /// Reported when the files change
+    key: String,
+    base: PathBuf,
+    patterns: Vec<String>,
+    ignore: GitIgnore,
+    snapshot: Snapshot,
+}
+
+impl WatchSet {
+    fn scan(&self) -> Result<Snapshot> {
+        let mut snapshot = Snapshot::new();
+        for pattern in &self.patterns {
+            for path in cache::expand(&self.base, pattern)? {
+                if self.ignore.is_ignored(&path) {
+                    continue;
+                }
+                if let Ok(meta) = fs::metadata(&path) {
+                    snapshot.insert(path, (meta.modified().ok(), meta.len()));
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Polls the `watch:` globs of tasks and reports which ones changed
+#[derive(Debug)]
+pub struct FileWatcher {
+    sets: Vec<WatchSet>,
+    pub poll_interval: Duration,
+    pub debounce: Duration,
+    last_poll: Option<Instant>,
+    /// Tasks with changes not reported yet, and when the last change was seen
+    changed: BTreeSet<String>,
+    last_change: Option<Instant>,
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self {
+            sets: Vec::new(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            debounce: DEFAULT_DEBOUNCE,
+            last_poll: None,
+            changed: BTreeSet::new(),
+            last_change: None,
+        }
+    }
+
+    /// Start watching a task's `watch:` globs (relative to its cwd)
+    ///
+    /// Pass the task with its variables already resolved. Does nothing if
+    /// the task watches no files.
+    pub fn watch(&mut self, task_id: &str, task: &Task) -> Result<()> {
+        let Some(patterns) = &task.watch else {
+            return Ok(());
+        };
+        let base = cache::task_dir(task.spawn_options()?.cwd.as_deref())?;
+        let mut set = WatchSet {
+            key: task_id.to_string(),
+            ignore: GitIgnore::load(&base),
+            base,
+            patterns: patterns.clone(),
+            snapshot: Snapshot::new(),
+        };
+        set.snapshot = set.scan()?;
+        self.sets.push(set);
+        Ok(())
+    }
+
+    /// Watch specific files, reported as `key` when they change
+    ///
+    /// Used for graph files; `.gitignore` does not apply.
+    pub fn watch_files(&mut self, key: &str, files: &[PathBuf]) -> Result<()> {
+        let cwd = std::env::current_dir()?;
+        let patterns = files
+            .iter()
+            .map(|file| {
+                let path = file.canonicalize().unwrap_or_else(|_| cwd.join(file));
+                glob::Pattern::escape(&path.to_string_lossy())
+            })
+            .collect();
+        let mut set = WatchSet {
+            key: key.to_string(),
+            base: cwd,
+            patterns,
+            ignore: GitIgnore::default(),
+            snapshot: Snapshot::new(),
+        };
+        set.snapshot = set.scan()?;
+        self.sets.push(set);
+        Ok(())
+    }
+
+    /// True if nothing is watched
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    /// Scan for changes (at most once per poll interval); returns the tasks
+    /// whose files changed, sorted, once the debounce period has passed
+    pub fn poll(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        if self
+            .last_poll
+            .is_some_and(|last| now.duration_since(last) < self.poll_interval)
+        {
+            return Vec::new();
+        }
+        self.last_poll = Some(now);
+
+        for set in &mut self.sets {
+            let snapshot = match set.scan() {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    log::debug!("Failed to scan watched files of {}: {}", set.key, e);
+                    continue;
+                }
+            };
+            if snapshot != set.snapshot {
+                set.snapshot = snapshot;
+                self.changed.insert(set.key.clone());
+                self.last_change = Some(now);
+            }
+        }
+
+        match self.last_change {
+            Some(at) if now.duration_since(at) >= self.debounce => {
+                self.last_change = None;
+                std::mem::take(&mut self.changed).into_iter().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// One `.gitignore` line
+#[derive(Debug)]
+struct IgnoreRule {
+    /// Directory of the `.gitignore` the rule came from
+    base: PathBuf,
+    pattern: glob::Pattern,
+    negate: bool,
+    /// Pattern ended with `/`: only matches directories
+    dir_only: bool,
+    /// Pattern contained a `/`: matched against the path from `base`
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(base: &Path, line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negate, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = glob::Pattern::new(line.trim_start_matches('/')).ok()?;
+        Some(Self {
+            base: base.to_path_buf(),
+            pattern,
+            negate,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// True if the file, or one of the directories leading to it, matches
+    fn matches(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        let components: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+
+        (0..components.len()).any(|i| {
+            let is_dir = i + 1 < components.len();
+            if self.dir_only && !is_dir {
+                return false;
+            }
+            if self.anchored {
+                self.pattern.matches_with(&components[..=i].join("/"), options)
+            } else {
+                self.pattern.matches_with(&components[i], options)
+            }
+        })
+    }
+}
+
+/// `.gitignore` rules of a directory and its parents, up to the repository root
+#[derive(Debug, Default)]
+struct GitIgnore {
+    /// Outermost file first, so deeper rules take precedence
+    rules: Vec<IgnoreRule>,
+}
+
+impl GitIgnore {
+    fn load(dir: &Path) -> Self {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let mut files = Vec::new();
+        for ancestor in dir.ancestors() {
+            if let Ok(content) = fs::read_to_string(ancestor.join(".gitignore")) {
+                files.push((ancestor.to_path_buf(), content));
+            }
+            if ancestor.join(".git").exists() {
+                break;
+            }
+        }
+
+        let rules = files
+            .iter()
+            .rev()
+            .flat_map(|(base, content)| content.lines().filter_map(|line| IgnoreRule::parse(base, line)))
+            .collect();
+        Self { rules }
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        if path
+            .components()
+            .any(|c| ALWAYS_IGNORED.iter().any(|name| c.as_os_str() == *name))
+        {
+            return true;
+        }
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        // The last matching rule wins
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.matches(&path) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_rules() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(
+            dir.path().join(".gitignore"),
+            "# build output\ntarget/\n*.log\n!keep.log\n/docs/generated\n",
+        )
+        .unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let ignore = GitIgnore::load(&root);
+
+        assert!(ignore.is_ignored(&root.join("target/debug/app")));
+        assert!(ignore.is_ignored(&root.join("src/debug.log")));
+        assert!(!ignore.is_ignored(&root.join("src/keep.log")));
+        assert!(ignore.is_ignored(&root.join("docs/generated/index.html")));
+        assert!(!ignore.is_ignored(&root.join("src/docs/generated")));
+        assert!(!ignore.is_ignored(&root.join("src/target")));
+        assert!(ignore.is_ignored(&root.join(".gidterm/sessions/latest.json")));
+        assert!(!ignore.is_ignored(&root.join("src/main.rs")));
+    }
+
+    #[test]
+    fn test_reports_changed_tasks_after_debounce() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.tmp\n").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "fn a() {}").unwrap();
+
+        let task = Task {
+            description: "Test".into(),
+            command: Some("cargo test".into()),
+            cwd: Some(dir.path().display().to_string()),
+            watch: Some(vec!["src/".into()]),
+            ..Default::default()
+        };
+        let mut watcher = FileWatcher::new();
+        watcher.poll_interval = Duration::ZERO;
+        watcher.debounce = Duration::ZERO;
+        watcher.watch("test", &task).unwrap();
+        assert!(watcher.poll().is_empty());
+
+        // Ignored files do not count
+        fs::write(dir.path().join("src/scratch.tmp"), "x").unwrap();
+        assert!(watcher.poll().is_empty());
+
+        fs::write(dir.path().join("src/new.rs"), "fn b() {}").unwrap();
+        watcher.debounce = Duration::from_secs(3600);
+        assert!(watcher.poll().is_empty());
+        watcher.debounce = Duration::ZERO;
+        assert_eq!(watcher.poll(), vec!["test"]);
+        assert!(watcher.poll().is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+//! Status write-back - persist task status changes to the source graph file
+//!
+//! Edits are line-based so comments, key order and the `nodes:` section
+//! survive untouched; only the `status:` field of the task is rewritten
+//! (or inserted if the task has none).
+
+use super::GraphTaskStatus;
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// `key: value  # comment`, capturing the prefix, the value and the rest
+static KEY_VALUE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^(\s*[^:]+:\s*)("[^"]*"|'[^']*'|[^#\s]*)(.*)$"#).unwrap());
+
+/// `status: value` inside a flow mapping, capturing the value
+static FLOW_STATUS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([{,]\s*status:\s*)("[^"]*"|'[^']*'|[^,}\s]+)"#).unwrap());
+
+/// Writes task statuses back to one or more graph files
+#[derive(Debug, Clone)]
+pub struct StatusWriter {
+    /// Graph file for single project mode
+    file: Option<PathBuf>,
+    /// Graph file per project for workspace mode (task IDs are "project:task")
+    projects: HashMap<String, PathBuf>,
+}
+
+impl StatusWriter {
+    /// Writer for a single graph file
+    pub fn single(path: impl Into<PathBuf>) -> Self {
+        Self {
+            file: Some(path.into()),
+            projects: HashMap::new(),
+        }
+    }
+
+    /// Writer for a workspace, given (project name, graph file) pairs
+    pub fn for_projects(projects: impl IntoIterator<Item = (String, PathBuf)>) -> Self {
+        Self {
+            file: None,
+            projects: projects.into_iter().collect(),
+        }
+    }
+
+    /// Persist the status of a task to its source file
+    pub fn write(&self, task_id: &str, status: &GraphTaskStatus) -> Result<()> {
+        let (path, local_id) = self.resolve(task_id)?;
+        let content = std::fs::read_to_string(path)?;
+        let updated = set_task_status(&content, local_id, status)?;
+        if updated != content {
+            std::fs::write(path, updated)?;
+        }
+        Ok(())
+    }
+
+    /// Find the file and file-local task ID for a (possibly namespaced) task ID
+    fn resolve<'a>(&'a self, task_id: &'a str) -> Result<(&'a Path, &'a str)> {
+        if let Some(file) = &self.file {
+            return Ok((file.as_path(), task_id));
+        }
+
+        let (project, local_id) = task_id
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Task {} has no project prefix", task_id))?;
+        let file = self
+            .projects
+            .get(project)
+            .ok_or_else(|| anyhow::anyhow!("No graph file known for project {}", project))?;
+        Ok((file.as_path(), local_id))
+    }
+}
+
+/// Rewrite the `status:` of one task in graph YAML, leaving everything else as-is
+pub fn set_task_status(content: &str, task_id: &str, status: &GraphTaskStatus) -> Result<String> {
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+
+    let tasks_line = lines
+        .iter()
+        .position(|l| top_level_key(l) == Some("tasks"))
+        .ok_or_else(|| anyhow::anyhow!("No top-level 'tasks:' section found"))?;
+
+    // Locate the task key within the tasks section
+    let mut key_indent = None;
+    let mut task_line = None;
+    for (idx, line) in lines.iter().enumerate().skip(tasks_line + 1) {
+        if is_blank_or_comment(line) {
+            continue;
+        }
+        let indent = indent_of(line);
+        if indent == 0 {
+            break; // Next top-level section
+        }
+        let key_indent = *key_indent.get_or_insert(indent);
+        if indent == key_indent && parse_key(line).as_deref() == Some(task_id) {
+            task_line = Some(idx);
+            break;
+        }
+    }
+    let (task_line, key_indent) = match (task_line, key_indent) {
+        (Some(line), Some(indent)) => (line, indent),
+        _ => anyhow::bail!("Task {} not found in tasks section", task_id),
+    };
+
+    let status = status.to_string();
+
+    // Flow style: `build: { command: make, status: pending }`
+    let (_, value) = lines[task_line].split_once(':').unwrap_or_default();
+    if value.trim_start().starts_with('{') {
+        lines[task_line] = set_flow_status(&lines[task_line], &status);
+        return Ok(join_lines(lines, content));
+    }
+
+    // Block style: find `status:` among the task's fields
+    let mut field_indent = None;
+    for (idx, line) in lines.iter().enumerate().skip(task_line + 1) {
+        if is_blank_or_comment(line) {
+            continue;
+        }
+        let indent = indent_of(line);
+        if indent <= key_indent {
+            break;
+        }
+        let field_indent = *field_indent.get_or_insert(indent);
+        if indent == field_indent && parse_key(line).as_deref() == Some("status") {
+            lines[idx] = replace_value(line, &status);
+            return Ok(join_lines(lines, content));
+        }
+    }
+
+    let indent = field_indent.unwrap_or(key_indent + 2);
+    lines.insert(task_line + 1, format!("{}status: {}", " ".repeat(indent), status));
+    Ok(join_lines(lines, content))
+}
+
+/// Key of a top-level mapping entry (no indentation)
+fn top_level_key(line: &str) -> Option<&str> {
+    if indent_of(line) != 0 || is_blank_or_comment(line) {
+        return None;
+    }
+    line.split_once(':').map(|(k, _)| k.trim())
+}
+
+/// Parse the (unquoted) key of a `key: value` line
+fn parse_key(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let key = if let Some(rest) = trimmed.strip_prefix('"') {
+        rest.split_once('"')?.0
+    } else if let Some(rest) = trimmed.strip_prefix('\'') {
+        rest.split_once('\'')?.0
+    } else {
+        trimmed.split_once(':')?.0.trim_end()
+    };
+    Some(key.to_string())
+}
+
+/// Replace the scalar value of a `key: value  # comment` line, keeping quotes and comment
+fn replace_value(line: &str, value: &str) -> String {
+    match KEY_VALUE.captures(line) {
+        Some(caps) => {
+            let old = &caps[2];
+            let new = if old.starts_with('"') {
+                format!("\"{}\"", value)
+            } else if old.starts_with('\'') {
+                format!("'{}'", value)
+            } else {
+                value.to_string()
+            };
+            format!("{}{}{}", &caps[1], new, &caps[3])
+        }
+        None => line.to_string(),
+    }
+}
+
+/// Set or insert `status` inside a flow mapping line
+fn set_flow_status(line: &str, status: &str) -> String {
+    if let Some(caps) = FLOW_STATUS.captures(line) {
+        let old = &caps[2];
+        let new = if old.starts_with('"') {
+            format!("\"{}\"", status)
+        } else if old.starts_with('\'') {
+            format!("'{}'", status)
+        } else {
+            status.to_string()
+        };
+        let range = caps.get(2).unwrap().range();
+        let mut out = line.to_string();
+        out.replace_range(range, &new);
+        out
+    } else {
+        line.replacen('{', &format!("{{ status: {},", status), 1)
+    }
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// Join lines back, keeping the original trailing newline (if any)
+fn join_lines(lines: Vec<String>, original: &str) -> String {
+    let mut out = lines.join("\n");
+    if original.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAPH: &str = r#"# Project graph
+metadata:
+  project: demo
+
+nodes:
+  build:
+    type: Feature
+    description: Build feature
+    status: active
+
+tasks:
+  # Compile everything
+  build:
+    description: Build
+    command: cargo build
+    status: "pending"   # updated by gidterm
+  test:
+    description: Test
+    command: cargo test
+    depends_on: [build]
+  lint: { description: Lint, command: cargo clippy, status: pending }
+"#;
+
+    #[test]
+    fn test_replace_existing_status() {
+        let out = set_task_status(GRAPH, "build", &GraphTaskStatus::Done).unwrap();
+        assert!(out.contains("    status: \"done\"   # updated by gidterm"));
+        // Node status and comments are untouched
+        assert!(out.contains("    status: active"));
+        assert!(out.contains("  # Compile everything"));
+        assert_eq!(out.lines().count(), GRAPH.lines().count());
+    }
+
+    #[test]
+    fn test_insert_missing_status() {
+        let out = set_task_status(GRAPH, "test", &GraphTaskStatus::InProgress).unwrap();
+        assert!(out.contains("  test:\n    status: in-progress\n    description: Test"));
+        let graph: crate::core::Graph = serde_yaml::from_str(&out).unwrap();
+        assert_eq!(graph.tasks["test"].status, GraphTaskStatus::InProgress);
+    }
+
+    #[test]
+    fn test_flow_style_status() {
+        let out = set_task_status(GRAPH, "lint", &GraphTaskStatus::Failed).unwrap();
+        assert!(out.contains("lint: { description: Lint, command: cargo clippy, status: failed }"));
+    }
+
+    #[test]
+    fn test_unknown_task() {
+        assert!(set_task_status(GRAPH, "deploy", &GraphTaskStatus::Done).is_err());
+    }
+}
@@ -1,16 +1,21 @@
 //! GidTerm CLI - Graph-Driven Semantic Terminal Controller
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use gidterm::app::{App, ViewMode};
-use gidterm::core::Graph;
-use gidterm::ports::PortRegistry;
+use clap::{Args, Parser, Subcommand};
+use gidterm::app::{App, GraphSource, ViewMode};
+use gidterm::core::{
+    resolve_task, Graph, GraphFormat, IssueSeverity, Plan, PolicyKind, SchedulingConfig, StatusWriter,
+    TaskSelection, VarContext,
+};
+use gidterm::ports::{PortManager, PortRegistry};
+use gidterm::session::Session;
 use gidterm::ui::{
     render_comparison_view, render_graph_view, render_live_dashboard, render_project_overview,
     render_terminal_view, TUI,
 };
 use gidterm::workspace::Workspace;
-use std::path::PathBuf;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 #[derive(Parser)]
@@ -24,18 +29,66 @@ struct Cli {
 enum Commands {
     /// Run tasks from a graph file (default behavior)
     Run {
-        /// Path to graph YAML file (auto-detects if not specified)
+        #[command(flatten)]
+        select: SelectArgs,
+
+        #[command(flatten)]
+        scheduling: SchedulingArgs,
+
+        /// Path to graph file: .yml, .toml or .json (auto-detects if not specified)
         #[arg(short, long)]
         graph: Option<PathBuf>,
 
         /// Workspace mode: discover and run all projects
         #[arg(short, long)]
         workspace: bool,
+
+        /// Write task status changes back to the graph file(s)
+        #[arg(long)]
+        write_back: bool,
+
+        /// Re-run tasks (and their dependents) when their `watch:` files change
+        #[arg(long)]
+        watch: bool,
+
+        /// Continue a previous session (default: the latest) instead of
+        /// starting from the graph's statuses
+        #[arg(long, value_name = "SESSION_ID", num_args = 0..=1, default_missing_value = "latest")]
+        resume: Option<String>,
+
+        /// Print the execution plan instead of running anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// With --dry-run, print the plan as JSON
+        #[arg(long, requires = "dry_run")]
+        json: bool,
+    },
+
+    /// Show the execution plan: waves, critical path and estimated duration
+    Plan {
+        #[command(flatten)]
+        select: SelectArgs,
+
+        #[command(flatten)]
+        scheduling: SchedulingArgs,
+
+        /// Path to graph file (.yml, .toml or .json)
+        #[arg(short, long)]
+        graph: Option<PathBuf>,
+
+        /// Workspace mode: plan all projects
+        #[arg(short, long)]
+        workspace: bool,
+
+        /// Print the plan as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Show status of tasks in a graph
     Status {
-        /// Path to graph YAML file
+        /// Path to graph file (.yml, .toml or .json)
         #[arg(short, long)]
         graph: Option<PathBuf>,
     },
@@ -59,11 +112,37 @@ enum Commands {
         /// Task ID to start
         task_id: String,
 
-        /// Path to graph YAML file
+        /// Path to graph file (.yml, .toml or .json)
         #[arg(short, long)]
         graph: Option<PathBuf>,
     },
 
+    /// Validate a graph file (non-zero exit code on errors)
+    Validate {
+        /// Path to graph file (.yml, .toml or .json)
+        #[arg(short, long)]
+        graph: Option<PathBuf>,
+    },
+
+    /// Convert a graph file between YAML, TOML and JSON
+    ///
+    /// Formats are taken from the file extensions. Comments are not kept.
+    Convert {
+        /// Graph file to read
+        input: PathBuf,
+
+        /// File to write (prints to stdout if omitted)
+        output: Option<PathBuf>,
+
+        /// Output format when printing to stdout: yaml, toml or json
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Overwrite the output file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+
     /// Show port allocations
     Ports {
         /// Clean up stale port allocations
@@ -72,6 +151,62 @@ enum Commands {
     },
 }
 
+/// Which tasks to run (`run` and `plan`)
+#[derive(Args)]
+struct SelectArgs {
+    /// Tasks to run along with their dependencies (default: every task)
+    targets: Vec<String>,
+
+    /// Only run tasks with one of these tags, plus their dependencies (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    tags: Vec<String>,
+
+    /// Only run tasks in one of these components, plus their dependencies (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    component: Vec<String>,
+
+    /// Leave these tasks out; their dependents run as if they succeeded
+    #[arg(long, value_delimiter = ',')]
+    exclude: Vec<String>,
+}
+
+impl From<SelectArgs> for TaskSelection {
+    fn from(args: SelectArgs) -> Self {
+        Self {
+            targets: args.targets,
+            tags: args.tags,
+            components: args.component,
+            exclude: args.exclude,
+        }
+    }
+}
+
+/// How ready tasks are started (`run` and `plan`); overrides the graph's
+/// `scheduling:` section
+#[derive(Args)]
+struct SchedulingArgs {
+    /// Order of ready tasks: priority, critical-path or fair-share
+    #[arg(long)]
+    policy: Option<PolicyKind>,
+
+    /// Maximum number of tasks running at once
+    #[arg(long)]
+    max_parallel: Option<NonZeroUsize>,
+}
+
+impl From<SchedulingArgs> for SchedulingConfig {
+    fn from(args: SchedulingArgs) -> Self {
+        Self {
+            policy: args.policy,
+            max_parallel: args.max_parallel,
+        }
+    }
+}
+
+/// Past sessions whose run times feed plan estimates and the critical-path
+/// policy
+const DURATION_HISTORY_SESSIONS: usize = 20;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
@@ -80,21 +215,69 @@ async fn main() -> Result<()> {
 
     match cli.command {
         None | Some(Commands::Run { .. }) => {
-            let (graph_path, workspace) = match &cli.command {
-                Some(Commands::Run { graph, workspace }) => (graph.clone(), *workspace),
-                _ => (None, false),
+            let (graph_path, workspace, write_back, watch, selection, scheduling, resume) = match cli.command {
+                Some(Commands::Run {
+                    select,
+                    scheduling,
+                    graph,
+                    workspace,
+                    resume,
+                    dry_run,
+                    json,
+                    write_back,
+                    watch,
+                }) => {
+                    if dry_run {
+                        return cmd_plan(graph, workspace, select.into(), scheduling.into(), json);
+                    }
+                    (graph, workspace, write_back, watch, select.into(), scheduling.into(), resume)
+                }
+                _ => (
+                    None,
+                    false,
+                    false,
+                    false,
+                    TaskSelection::default(),
+                    SchedulingConfig::default(),
+                    None,
+                ),
             };
-            run_tui(graph_path, workspace).await
+            run_tui(graph_path, workspace, write_back, watch, selection, scheduling, resume).await
+        }
+        Some(Commands::Plan { select, scheduling, graph, workspace, json }) => {
+            cmd_plan(graph, workspace, select.into(), scheduling.into(), json)
         }
         Some(Commands::Status { graph }) => cmd_status(graph),
         Some(Commands::Init { output }) => cmd_init(&output),
         Some(Commands::History { count }) => cmd_history(count),
         Some(Commands::Start { task_id, graph }) => cmd_start(&task_id, graph).await,
+        Some(Commands::Validate { graph }) => cmd_validate(graph),
+        Some(Commands::Convert { input, output, to, force }) => {
+            cmd_convert(&input, output.as_deref(), to.as_deref(), force)
+        }
         Some(Commands::Ports { cleanup }) => cmd_ports(cleanup),
     }
 }
 
-async fn run_tui(graph_path: Option<PathBuf>, workspace: bool) -> Result<()> {
+/// Narrow the run to the tasks picked on the command line, if any
+fn select_tasks(app: &mut App, selection: TaskSelection) -> Result<()> {
+    if selection.is_empty() {
+        return Ok(());
+    }
+    let count = app.select_tasks(selection)?;
+    log::info!("🎯 Running {} selected task(s)", count);
+    Ok(())
+}
+
+async fn run_tui(
+    graph_path: Option<PathBuf>,
+    workspace: bool,
+    write_back: bool,
+    watch: bool,
+    selection: TaskSelection,
+    scheduling: SchedulingConfig,
+    resume: Option<String>,
+) -> Result<()> {
     log::info!("🚀 GidTerm v{} (Live Mode)", env!("CARGO_PKG_VERSION"));
 
     let mut app = if workspace {
@@ -106,25 +289,88 @@ async fn run_tui(graph_path: Option<PathBuf>, workspace: bool) -> Result<()> {
             workspace.project_count(),
             workspace.total_task_count()
         );
-        App::from_workspace(&workspace)
+        workspace.ensure_valid()?;
+        let mut app = App::from_workspace(&workspace);
+        select_tasks(&mut app, selection)?;
+        if write_back {
+            app.scheduler.set_status_writer(StatusWriter::for_projects(
+                workspace
+                    .projects
+                    .values()
+                    .filter(|p| GraphFormat::from_path(&p.graph_path).ok() == Some(GraphFormat::Yaml))
+                    .map(|p| (p.name.clone(), p.graph_path.clone())),
+            ));
+        }
+        if let Err(e) = app.enable_reload(GraphSource::Workspace(workspace)) {
+            log::warn!("Failed to watch graph files: {}", e);
+        }
+        app
     } else {
-        let graph = if let Some(path) = graph_path {
-            log::info!("Loading graph from: {}", path.display());
-            Graph::from_file(&path)?
+        let path = if let Some(path) = graph_path {
+            path
         } else {
             log::info!("Auto-detecting graph file...");
-            Graph::auto_load()?
+            Graph::detect_path()?
         };
+        log::info!("Loading graph from: {}", path.display());
+        let graph = Graph::from_file(&path)?;
         log::info!("Loaded {} nodes, {} tasks", graph.nodes.len(), graph.tasks.len());
-        App::new(graph)
+        let mut app = App::new(graph);
+        select_tasks(&mut app, selection)?;
+        if write_back {
+            if GraphFormat::from_path(&path)? != GraphFormat::Yaml {
+                anyhow::bail!("--write-back is only supported for YAML graphs ({})", path.display());
+            }
+            log::info!("Writing task status changes back to {}", path.display());
+            app.scheduler.set_status_writer(StatusWriter::single(path.clone()));
+        }
+        if let Err(e) = app.enable_reload(GraphSource::File(path)) {
+            log::warn!("Failed to watch graph file: {}", e);
+        }
+        app
     };
 
+    // Past run times, in case the policy is (or is reloaded as) critical-path
+    let history = Session::average_durations(&Session::load_recent(DURATION_HISTORY_SESSIONS)?);
+    app.scheduler.configure(&scheduling, &history);
+    let scheduling = app.scheduler.scheduling();
+    if scheduling != SchedulingConfig::default() {
+        log::info!(
+            "📋 Scheduling: {} policy, {}",
+            app.scheduler.policy_name(),
+            match scheduling.max_parallel {
+                Some(max) => format!("at most {} task(s) at once", max),
+                None => "no parallelism limit".to_string(),
+            }
+        );
+    }
+
+    if let Some(id) = resume {
+        let session = if id == "latest" {
+            Session::load_latest()?
+        } else {
+            Session::load(&id)?
+        };
+        app.resume(session)?;
+    }
+
+    if watch {
+        let watched = app.enable_watch()?;
+        if watched == 0 {
+            log::warn!("--watch given, but no task has `watch:` globs");
+        } else {
+            log::info!("👀 Watching files of {} task(s)", watched);
+        }
+    }
+
     app.start_ready_tasks().await?;
 
     let mut tui = TUI::new()?;
 
     loop {
         app.process_events();
+        app.poll_reload();
+        app.poll_watch();
         app.start_ready_tasks().await?;
 
         tui.terminal().draw(|f| {
@@ -153,6 +399,7 @@ async fn run_tui(graph_path: Option<PathBuf>, workspace: bool) -> Result<()> {
 
     log::info!("Shutting down...");
     app.executor.stop_all();
+    app.scheduler.interrupt();
     app.session.end();
     if let Err(e) = app.session.save() {
         log::warn!("Failed to save final session: {}", e);
@@ -161,6 +408,41 @@ async fn run_tui(graph_path: Option<PathBuf>, workspace: bool) -> Result<()> {
     Ok(())
 }
 
+fn cmd_plan(
+    graph_path: Option<PathBuf>,
+    workspace: bool,
+    selection: TaskSelection,
+    scheduling: SchedulingConfig,
+    json: bool,
+) -> Result<()> {
+    let mut graph = if workspace {
+        let workspace = Workspace::discover(&std::env::current_dir()?)?;
+        workspace.ensure_valid()?;
+        workspace.to_unified_graph()
+    } else {
+        let path = match graph_path {
+            Some(path) => path,
+            None => Graph::detect_path()?,
+        };
+        Graph::from_file(&path)?
+    };
+    graph.scheduling = Some(scheduling.or(graph.scheduling.unwrap_or_default()));
+    let graph = if selection.is_empty() {
+        graph
+    } else {
+        graph.select(&selection)?
+    };
+
+    let history = Session::average_durations(&Session::load_recent(DURATION_HISTORY_SESSIONS)?);
+    let plan = Plan::build(&graph, &history);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        println!("{}", plan);
+    }
+    Ok(())
+}
+
 fn cmd_status(graph_path: Option<PathBuf>) -> Result<()> {
     let graph = if let Some(path) = graph_path {
         Graph::from_file(&path)?
@@ -262,17 +544,53 @@ async fn cmd_start(task_id: &str, graph_path: Option<PathBuf>) -> Result<()> {
     let task = graph.get_task(task_id)
         .ok_or_else(|| anyhow::anyhow!("Task '{}' not found", task_id))?;
 
+    // Same port and variables the TUI would give the task
+    let project = graph
+        .metadata
+        .as_ref()
+        .map(|m| m.project.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut port_manager = PortManager::default();
+    if let Err(e) = port_manager.allocate(&project, None) {
+        log::warn!("Failed to allocate port for {}: {}", project, e);
+    }
+    let ctx = VarContext {
+        vars: graph.vars.clone().unwrap_or_default(),
+        task_id: task_id.to_string(),
+        project_name: Some(project.clone()),
+        project_root: std::env::current_dir()?,
+        port: port_manager.get_port(&project),
+        // Upstream values from the most recent session, if any
+        task_outputs: Session::load_latest()
+            .map(|session| {
+                session
+                    .tasks
+                    .keys()
+                    .filter_map(|id| Some((id.clone(), session.captured(id)?.clone())))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    let task = resolve_task(task, &ctx)?;
+
     let command = task.command.as_ref()
         .ok_or_else(|| anyhow::anyhow!("Task '{}' has no command", task_id))?;
 
     println!("Starting task: {} ({})", task_id, command);
     println!("  {}", task.description);
 
-    let status = tokio::process::Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .status()
-        .await?;
+    let mut options = task.spawn_options()?;
+    for (key, value) in port_manager.get_env(&project) {
+        options.env.entry(key).or_insert(value);
+    }
+    let argv = options.shell.argv(command);
+    let mut cmd = tokio::process::Command::new(&argv[0]);
+    cmd.args(&argv[1..]).envs(&options.env);
+    if let Some(cwd) = &options.cwd {
+        cmd.current_dir(cwd);
+    }
+
+    let status = cmd.status().await?;
 
     if status.success() {
         println!("\nTask '{}' completed successfully.", task_id);
@@ -283,6 +601,59 @@ async fn cmd_start(task_id: &str, graph_path: Option<PathBuf>) -> Result<()> {
     Ok(())
 }
 
+fn cmd_validate(graph_path: Option<PathBuf>) -> Result<()> {
+    let path = match graph_path {
+        Some(path) => path,
+        None => Graph::detect_path()?,
+    };
+
+    let graph = Graph::from_file_unchecked(&path)?;
+    let report = graph.validate();
+
+    println!("Validating {} ({} tasks)", path.display(), graph.tasks.len());
+    for issue in &report.issues {
+        let icon = match issue.severity() {
+            IssueSeverity::Error => "✗",
+            IssueSeverity::Warning => "⚠",
+        };
+        println!("  {} {}: {}", icon, issue.severity(), issue);
+    }
+
+    let errors = report.errors().len();
+    let warnings = report.warnings().len();
+    if errors > 0 {
+        anyhow::bail!("validation failed: {} error(s), {} warning(s)", errors, warnings);
+    }
+
+    println!("\nGraph is valid ({} warning(s)).", warnings);
+    Ok(())
+}
+
+fn cmd_convert(input: &Path, output: Option<&Path>, to: Option<&str>, force: bool) -> Result<()> {
+    // Parse the file as written: includes and matrices stay unexpanded
+    let content = std::fs::read_to_string(input)?;
+    let graph: Graph = GraphFormat::from_path(input)?.parse(&content, input)?;
+
+    let format = match (output, to) {
+        (Some(path), _) => GraphFormat::from_path(path)?,
+        (None, Some(name)) => GraphFormat::from_path(Path::new(&format!("graph.{}", name)))?,
+        (None, None) => anyhow::bail!("Specify an output file or --to yaml|toml|json"),
+    };
+    let rendered = format.render(&graph)?;
+
+    match output {
+        Some(path) => {
+            if path.exists() && !force {
+                anyhow::bail!("File already exists: {}. Use --force to overwrite.", path.display());
+            }
+            std::fs::write(path, rendered)?;
+            println!("✓ Converted {} to {} ({})", input.display(), path.display(), format);
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
 fn cmd_ports(cleanup: bool) -> Result<()> {
     let mut registry = PortRegistry::load()?;
 
@@ -7,9 +7,19 @@
 //! - Accuracy saturation
 //! - Error spikes
 //! - Build failures
+//! - Crash loops (a restarted task that keeps exiting)
 
 use super::history::TaskMetricHistory;
 use super::TaskMetrics;
+use std::time::{Duration, Instant};
+
+/// Exits within this window count towards a crash loop
+pub const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(120);
+
+/// Number of exits within the window that make a crash loop
+pub const CRASH_LOOP_EXITS: usize = 3;
+
+const CRASH_LOOP_MESSAGE: &str = "Crash loop";
 
 /// Severity of an advisory
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -39,6 +49,13 @@ pub struct Advisory {
     pub auto_action: Option<String>,
 }
 
+impl Advisory {
+    /// True for the advisory produced by `SmartAdvisor::evaluate_crashes`
+    pub fn is_crash_loop(&self) -> bool {
+        self.message.starts_with(CRASH_LOOP_MESSAGE)
+    }
+}
+
 /// Smart advisor that analyzes metrics and emits suggestions
 pub struct SmartAdvisor {
     rules: Vec<Box<dyn AdvisoryRule + Send + Sync>>,
@@ -79,6 +96,30 @@ impl SmartAdvisor {
             .filter_map(|rule| rule.evaluate(metrics, history))
             .collect()
     }
+
+    /// Detect a crash loop from the times a restarted task exited
+    pub fn evaluate_crashes(&self, exits: &[Instant], last_reason: Option<&str>) -> Option<Advisory> {
+        let now = Instant::now();
+        let recent = exits
+            .iter()
+            .filter(|t| now.saturating_duration_since(**t) <= CRASH_LOOP_WINDOW)
+            .count();
+        if recent < CRASH_LOOP_EXITS {
+            return None;
+        }
+        Some(Advisory {
+            severity: Severity::Critical,
+            message: format!(
+                "{}: exited {} times in the last {}s ({})",
+                CRASH_LOOP_MESSAGE,
+                recent,
+                CRASH_LOOP_WINDOW.as_secs(),
+                last_reason.unwrap_or("unknown reason")
+            ),
+            suggestion: "Check the output of the last run; restarts back off until it stays up".to_string(),
+            auto_action: None,
+        })
+    }
 }
 
 impl Default for SmartAdvisor {
@@ -298,4 +339,20 @@ mod tests {
         let advisories = advisor.evaluate(&metrics, None);
         assert!(advisories.iter().all(|a| !a.message.contains("still high")));
     }
+
+    #[test]
+    fn test_crash_loop_detection() {
+        let advisor = SmartAdvisor::new();
+        let now = Instant::now();
+
+        assert!(advisor.evaluate_crashes(&[now, now], Some("exit code 1")).is_none());
+
+        let advisory = advisor
+            .evaluate_crashes(&[now, now, now], Some("exit code 1"))
+            .unwrap();
+        assert!(advisory.is_crash_loop());
+        assert_eq!(advisory.severity, Severity::Critical);
+        assert!(advisory.message.contains("3 times"));
+        assert!(advisory.message.contains("exit code 1"));
+    }
 }
@@ -6,9 +6,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 const SESSIONS_DIR: &str = ".gidterm/sessions";
 
+/// Recent runs averaged for a task's expected duration
+const DURATION_SAMPLES: usize = 5;
+
 /// A session represents one gidterm run
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -16,6 +20,9 @@ pub struct Session {
     pub project: String,
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
+    /// When the session was continued with `run --resume`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resumed_at: Vec<DateTime<Utc>>,
     pub tasks: HashMap<String, TaskHistory>,
 }
 
@@ -34,6 +41,29 @@ pub struct TaskRun {
     pub status: TaskStatus,
     pub output: Vec<String>,
     pub exit_code: Option<i32>,
+    /// 1 for the first run, incremented for each retry
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+    /// Values captured for dependents (`outputs_capture` / `$GIDTERM_OUTPUT`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub captured: HashMap<String, String>,
+    /// Decision on a gate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<Approval>,
+}
+
+/// Who approved or rejected a gate, and when
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Approval {
+    pub approved: bool,
+    pub approver: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+fn first_attempt() -> u32 {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -42,6 +72,22 @@ pub enum TaskStatus {
     Running,
     Done,
     Failed,
+    /// Terminated after exceeding its timeout
+    TimedOut,
+    /// Not run because of how its dependencies finished
+    Skipped,
+    /// Not run because its inputs and outputs are unchanged
+    Cached,
+    /// Stopped to run again after a watched file changed
+    Superseded,
+    /// Gate waiting for a decision
+    AwaitingApproval,
+    /// Gate that was approved
+    Approved,
+    /// Gate that was rejected
+    Rejected,
+    /// Still running (or awaiting approval) when gidterm stopped
+    Interrupted,
 }
 
 impl Session {
@@ -53,6 +99,7 @@ impl Session {
             project,
             started_at: Utc::now(),
             ended_at: None,
+            resumed_at: Vec::new(),
             tasks: HashMap::new(),
         }
     }
@@ -71,13 +118,13 @@ impl Session {
         let json = serde_json::to_string_pretty(self)?;
         fs::write(&path, json)?;
 
-        // Update latest symlink
+        // Update latest symlink (relative to the sessions directory)
         let latest_path = Path::new(SESSIONS_DIR).join("latest.json");
         #[cfg(unix)]
         {
             use std::os::unix::fs as unix_fs;
             let _ = fs::remove_file(&latest_path); // Ignore if doesn't exist
-            unix_fs::symlink(&path, &latest_path)?;
+            unix_fs::symlink(format!("{}.json", self.id), &latest_path)?;
         }
 
         Ok(())
@@ -86,7 +133,8 @@ impl Session {
     /// Load session from disk
     pub fn load(id: &str) -> Result<Self> {
         let path = Path::new(SESSIONS_DIR).join(format!("{}.json", id));
-        let content = fs::read_to_string(&path)?;
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read session {}: {}", id, e))?;
         let session: Session = serde_json::from_str(&content)?;
         Ok(session)
     }
@@ -127,8 +175,66 @@ impl Session {
         Ok(sessions)
     }
 
+    /// Load the most recent sessions, newest first (unreadable ones are skipped)
+    pub fn load_recent(count: usize) -> Result<Vec<Self>> {
+        Ok(Self::list_all()?
+            .into_iter()
+            .take(count)
+            .filter_map(|id| Self::load(&id).ok())
+            .collect())
+    }
+
+    /// Average duration of each task's successful runs, over its last
+    /// `DURATION_SAMPLES` runs in these sessions
+    pub fn average_durations(sessions: &[Session]) -> HashMap<String, Duration> {
+        let mut runs: HashMap<&str, Vec<(DateTime<Utc>, Duration)>> = HashMap::new();
+        for session in sessions {
+            for (task_id, history) in &session.tasks {
+                for run in &history.runs {
+                    let Some(ended) = run.ended else {
+                        continue;
+                    };
+                    if run.status != TaskStatus::Done {
+                        continue;
+                    }
+                    if let Ok(duration) = (ended - run.started).to_std() {
+                        runs.entry(task_id).or_default().push((run.started, duration));
+                    }
+                }
+            }
+        }
+
+        runs.into_iter()
+            .map(|(task_id, mut samples)| {
+                samples.sort_by_key(|(started, _)| std::cmp::Reverse(*started));
+                samples.truncate(DURATION_SAMPLES);
+                let total: Duration = samples.iter().map(|(_, d)| *d).sum();
+                (task_id.to_string(), total / samples.len() as u32)
+            })
+            .collect()
+    }
+
+    /// Continue this session: runs left open are marked interrupted, and
+    /// new runs are added to its history
+    pub fn resume(&mut self) {
+        let now = Utc::now();
+        for run in self.tasks.values_mut().filter_map(|h| h.runs.last_mut()) {
+            if run.ended.is_none() {
+                run.ended = Some(now);
+                run.status = TaskStatus::Interrupted;
+            }
+        }
+        self.ended_at = None;
+        self.resumed_at.push(now);
+    }
+
     /// Start tracking a task
     pub fn start_task(&mut self, task_id: String) {
+        self.start_attempt(task_id, 1);
+    }
+
+    /// Start tracking a retry of a task (`attempt` is 1-based)
+    pub fn start_attempt(&mut self, task_id: String, attempt: u32) {
         let task_history = self.tasks.entry(task_id.clone()).or_insert(TaskHistory {
             task_id: task_id.clone(),
             runs: Vec::new(),
@@ -140,9 +246,68 @@ impl Session {
             status: TaskStatus::Running,
             output: Vec::new(),
             exit_code: None,
+            attempt,
+            captured: HashMap::new(),
+            approval: None,
         });
     }
 
+    /// Record a task that was skipped without running
+    pub fn skip_task(&mut self, task_id: String) {
+        self.record_without_run(task_id, TaskStatus::Skipped);
+    }
+
+    /// Record a task that was up to date and not run
+    pub fn cache_task(&mut self, task_id: String) {
+        self.record_without_run(task_id, TaskStatus::Cached);
+    }
+
+    fn record_without_run(&mut self, task_id: String, status: TaskStatus) {
+        let now = Utc::now();
+        let task_history = self.tasks.entry(task_id.clone()).or_insert(TaskHistory {
+            task_id,
+            runs: Vec::new(),
+        });
+
+        task_history.runs.push(TaskRun {
+            started: now,
+            ended: Some(now),
+            status,
+            output: Vec::new(),
+            exit_code: None,
+            attempt: 1,
+            captured: HashMap::new(),
+            approval: None,
+        });
+    }
+
+    /// Record a gate that started waiting for approval
+    pub fn await_approval(&mut self, task_id: String) {
+        self.start_task(task_id.clone());
+        if let Some(last_run) = self.tasks.get_mut(&task_id).and_then(|h| h.runs.last_mut()) {
+            last_run.status = TaskStatus::AwaitingApproval;
+        }
+    }
+
+    /// Record the decision on a gate awaiting approval
+    pub fn record_approval(&mut self, task_id: &str, approval: Approval) {
+        let status = if approval.approved {
+            TaskStatus::Approved
+        } else {
+            TaskStatus::Rejected
+        };
+        self.end_task(task_id, status, None);
+        if let Some(last_run) = self.tasks.get_mut(task_id).and_then(|h| h.runs.last_mut()) {
+            last_run.ended = Some(approval.at);
+            last_run.approval = Some(approval);
+        }
+    }
+
+    /// Decision on the most recent run of a gate
+    pub fn approval(&self, task_id: &str) -> Option<&Approval> {
+        self.tasks.get(task_id)?.runs.last()?.approval.as_ref()
+    }
+
     /// End task with status
     pub fn end_task(&mut self, task_id: &str, status: TaskStatus, exit_code: Option<i32>) {
         if let Some(task_history) = self.tasks.get_mut(task_id) {
@@ -154,6 +319,29 @@ impl Session {
         }
     }
 
+    /// Store values captured during the most recent run of a task
+    pub fn set_captured(&mut self, task_id: &str, values: HashMap<String, String>) {
+        if let Some(last_run) = self.tasks.get_mut(task_id).and_then(|h| h.runs.last_mut()) {
+            last_run.captured = values;
+        }
+    }
+
+    /// Captured values of a task whose most recent run succeeded (or was cached)
+    pub fn captured(&self, task_id: &str) -> Option<&HashMap<String, String>> {
+        let last_run = self.tasks.get(task_id)?.runs.last()?;
+        matches!(last_run.status, TaskStatus::Done | TaskStatus::Cached).then_some(&last_run.captured)
+    }
+
+    /// Status of the most recent run of a task
+    pub fn last_run_status(&self, task_id: &str) -> Option<&TaskStatus> {
+        self.tasks.get(task_id)?.runs.last().map(|r| &r.status)
+    }
+
+    /// Attempt number of the most recent run of a task
+    pub fn last_run_attempt(&self, task_id: &str) -> Option<u32> {
+        self.tasks.get(task_id)?.runs.last().map(|r| r.attempt)
+    }
+
     /// Add output line to current task run
     pub fn add_output(&mut self, task_id: &str, line: String) {
         if let Some(task_history) = self.tasks.get_mut(task_id) {
@@ -196,4 +384,76 @@ mod tests {
         assert_eq!(task.runs[0].status, TaskStatus::Done);
         assert_eq!(task.runs[0].exit_code, Some(0));
     }
+
+    #[test]
+    fn test_retry_attempts_are_separate_runs() {
+        let mut session = Session::new("test".to_string());
+        session.start_task("install".to_string());
+        session.end_task("install", TaskStatus::Failed, Some(1));
+        session.start_attempt("install".to_string(), 2);
+
+        let runs = &session.tasks["install"].runs;
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].attempt, 1);
+        assert_eq!(runs[0].status, TaskStatus::Failed);
+        assert_eq!(session.last_run_attempt("install"), Some(2));
+        assert_eq!(session.last_run_status("install"), Some(&TaskStatus::Running));
+    }
+
+    #[test]
+    fn test_gate_approval_is_recorded() {
+        let mut session = Session::new("test".to_string());
+        session.await_approval("deploy".to_string());
+        assert_eq!(session.last_run_status("deploy"), Some(&TaskStatus::AwaitingApproval));
+        assert!(session.approval("deploy").is_none());
+
+        let approval = Approval {
+            approved: false,
+            approver: "alice".to_string(),
+            reason: Some("release freeze".to_string()),
+            at: Utc::now(),
+        };
+        session.record_approval("deploy", approval.clone());
+        let run = &session.tasks["deploy"].runs[0];
+        assert_eq!(run.status, TaskStatus::Rejected);
+        assert_eq!(run.ended, Some(approval.at));
+        assert_eq!(session.approval("deploy"), Some(&approval));
+
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(json.contains("\"approver\":\"alice\""));
+    }
+
+    #[test]
+    fn test_average_durations() {
+        let mut session = Session::new("test".to_string());
+        for (secs, status) in [(10, TaskStatus::Done), (20, TaskStatus::Done), (500, TaskStatus::Failed)] {
+            session.start_task("build".to_string());
+            session.end_task("build", status, None);
+            let run = session.tasks.get_mut("build").unwrap().runs.last_mut().unwrap();
+            run.ended = Some(run.started + chrono::Duration::seconds(secs));
+        }
+        session.start_task("dev".to_string());
+
+        let durations = Session::average_durations(&[session]);
+        assert_eq!(durations.get("build"), Some(&Duration::from_secs(15)));
+        assert!(!durations.contains_key("dev"));
+    }
+
+    #[test]
+    fn test_resume_closes_open_runs() {
+        let mut session = Session::new("test".to_string());
+        session.start_task("build".to_string());
+        session.end_task("build", TaskStatus::Done, Some(0));
+        session.start_task("dev".to_string());
+        session.await_approval("deploy".to_string());
+        session.end();
+
+        session.resume();
+        assert!(session.ended_at.is_none());
+        assert_eq!(session.resumed_at.len(), 1);
+        assert_eq!(session.last_run_status("build"), Some(&TaskStatus::Done));
+        assert_eq!(session.last_run_status("dev"), Some(&TaskStatus::Interrupted));
+        assert_eq!(session.last_run_status("deploy"), Some(&TaskStatus::Interrupted));
+        assert!(session.tasks["dev"].runs[0].ended.is_some());
+    }
 }
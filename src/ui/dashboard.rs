@@ -56,6 +56,10 @@ impl DashboardView {
                     GraphTaskStatus::Failed => "✗",
                     GraphTaskStatus::Pending => "□",
                     GraphTaskStatus::Planned => "○",
+                    GraphTaskStatus::Skipped => "⊘",
+                    GraphTaskStatus::Cached => "≡",
+                    GraphTaskStatus::Ready => "●",
+                    GraphTaskStatus::AwaitingApproval => "⏸",
                 };
 
                 let status_color = match task.status {
@@ -64,6 +68,10 @@ impl DashboardView {
                     GraphTaskStatus::Failed => Color::Red,
                     GraphTaskStatus::Pending => Color::Gray,
                     GraphTaskStatus::Planned => Color::DarkGray,
+                    GraphTaskStatus::Skipped => Color::DarkGray,
+                    GraphTaskStatus::Cached => Color::Cyan,
+                    GraphTaskStatus::Ready => Color::LightGreen,
+                    GraphTaskStatus::AwaitingApproval => Color::LightMagenta,
                 };
 
                 let priority_badge = task.priority.as_ref().map(|p| match p.as_str() {
@@ -1,8 +1,10 @@
 //! Live dashboard with real-time updates and semantic metrics
 
 use crate::app::App;
-use crate::core::GraphTaskStatus;
+use crate::core::{GraphTaskStatus, RetryPolicy};
 use crate::semantic::MetricValue;
+use crate::session::TaskStatus;
+use super::header_block;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -10,6 +12,7 @@ use ratatui::{
     widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Wrap},
     Frame,
 };
+use std::time::Instant;
 
 /// Render the live dashboard
 pub fn render_live_dashboard(f: &mut Frame, app: &App) {
@@ -26,7 +29,7 @@ pub fn render_live_dashboard(f: &mut Frame, app: &App) {
     render_header(f, app, chunks[0]);
     render_task_list(f, app, chunks[1]);
     render_task_detail(f, app, chunks[2]);
-    render_footer(f, chunks[3]);
+    render_footer(f, app, chunks[3]);
 }
 
 fn render_header(f: &mut Frame, app: &App, area: Rect) {
@@ -56,14 +59,28 @@ fn render_header(f: &mut Frame, app: &App, area: Rect) {
         .values()
         .filter(|t| t.status == GraphTaskStatus::Failed)
         .count();
+    let skipped = graph
+        .all_tasks()
+        .values()
+        .filter(|t| t.status == GraphTaskStatus::Skipped)
+        .count();
+    let cached = graph
+        .all_tasks()
+        .values()
+        .filter(|t| t.status == GraphTaskStatus::Cached)
+        .count();
 
-    let status_text = format!(
-        "{} | Running: {} | Done: {} | Failed: {} | Total: {}",
-        title, running, done, failed, total
+    let mut status_text = format!(
+        "{} | Running: {} | Done: {} | Cached: {} | Failed: {} | Skipped: {} | Total: {}",
+        title, running, done, cached, failed, skipped, total
     );
+    let awaiting = app.scheduler.awaiting_approval().len();
+    if awaiting > 0 {
+        status_text.push_str(&format!(" | ⏸ Awaiting approval: {}", awaiting));
+    }
 
     let header = Paragraph::new(status_text)
-        .block(Block::default().borders(Borders::ALL))
+        .block(header_block(app))
         .style(Style::default().fg(Color::Cyan));
 
     f.render_widget(header, area);
@@ -123,6 +140,19 @@ fn render_task_item<'a>(app: &'a App, task_id: &str, idx: usize) -> ListItem<'a>
         GraphTaskStatus::Failed => "✗",
         GraphTaskStatus::Pending => "□",
         GraphTaskStatus::Planned => "○",
+        GraphTaskStatus::Skipped => "⊘",
+        GraphTaskStatus::Cached => "≡",
+        GraphTaskStatus::Ready => "●",
+        GraphTaskStatus::AwaitingApproval => "⏸",
+    };
+
+    // A failed task may have been killed by its timeout
+    let timed_out = task.status == GraphTaskStatus::Failed
+        && app.session.last_run_status(task_id) == Some(&TaskStatus::TimedOut);
+    let (status_icon, status_label) = if timed_out {
+        ("⏱", "timed-out".to_string())
+    } else {
+        (status_icon, task.status.to_string())
     };
 
     let status_color = match task.status {
@@ -131,6 +161,10 @@ fn render_task_item<'a>(app: &'a App, task_id: &str, idx: usize) -> ListItem<'a>
         GraphTaskStatus::Failed => Color::Red,
         GraphTaskStatus::Pending => Color::Gray,
         GraphTaskStatus::Planned => Color::DarkGray,
+        GraphTaskStatus::Skipped => Color::DarkGray,
+        GraphTaskStatus::Cached => Color::Cyan,
+        GraphTaskStatus::Ready => Color::LightGreen,
+        GraphTaskStatus::AwaitingApproval => Color::LightMagenta,
     };
 
     let priority_badge = task
@@ -144,6 +178,40 @@ fn render_task_item<'a>(app: &'a App, task_id: &str, idx: usize) -> ListItem<'a>
         })
         .unwrap_or("");
 
+    // Retry attempt, e.g. " ↻ 2/4" or " ↻ 3/4 in 8s" while backing off
+    let policy = RetryPolicy::from_task(task);
+    let retry_badge = match app.pending_retries.get(task_id) {
+        Some((at, attempt)) => format!(
+            " ↻ {}/{} in {}s",
+            attempt,
+            policy.max_attempts(),
+            at.saturating_duration_since(Instant::now()).as_secs() + 1
+        ),
+        None => match app.session.last_run_attempt(task_id) {
+            Some(attempt) if attempt > 1 => format!(" ↻ {}/{}", attempt, policy.max_attempts()),
+            _ => String::new(),
+        },
+    };
+
+    // Restarts and why the process last exited, e.g. " ⟳ 3 (exit code 1)"
+    // or " ⟳ 3 in 4s (exit code 1)" while backing off
+    let restart_badge = match app.restarts.get(task_id) {
+        Some(history) if history.count > 0 => {
+            let countdown = app
+                .pending_restarts
+                .get(task_id)
+                .map(|at| format!(" in {}s", at.saturating_duration_since(Instant::now()).as_secs() + 1))
+                .unwrap_or_default();
+            let reason = history
+                .last_reason
+                .as_deref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default();
+            format!(" ⟳ {}{}{}", history.count, countdown, reason)
+        }
+        _ => String::new(),
+    };
+
     // Output line count
     let output_count = app
         .task_outputs
@@ -153,7 +221,7 @@ fn render_task_item<'a>(app: &'a App, task_id: &str, idx: usize) -> ListItem<'a>
 
     // Display name — strip project prefix in workspace mode
     let display_name = if app.workspace_mode {
-        task_id.split(':').nth(1).unwrap_or(task_id)
+        task_id.split_once(':').map(|(_, t)| t).unwrap_or(task_id)
     } else {
         task_id
     };
@@ -219,7 +287,9 @@ fn render_task_item<'a>(app: &'a App, task_id: &str, idx: usize) -> ListItem<'a>
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(priority_badge.to_string()),
-        Span::styled(format!(" [{}]", task.status.to_string()), Style::default().fg(status_color)),
+        Span::styled(format!(" [{}]", status_label), Style::default().fg(status_color)),
+        Span::styled(retry_badge, Style::default().fg(Color::Magenta)),
+        Span::styled(restart_badge, Style::default().fg(Color::LightRed)),
         Span::styled(output_count, Style::default().fg(Color::DarkGray)),
         Span::styled(metrics_summary, Style::default().fg(Color::Cyan)),
     ]);
@@ -290,7 +360,22 @@ fn render_output_panel(f: &mut Frame, app: &App, task_id: &str, area: Rect) {
     let height = area.height.saturating_sub(2) as usize; // minus borders
     let output_lines = app.get_task_output(task_id, height);
 
-    let text = if output_lines.is_empty() {
+    let is_gate = app.scheduler.graph().get_task(task_id).is_some_and(|t| t.is_gate());
+    let text = if is_gate {
+        match app.session.approval(task_id) {
+            Some(approval) => format!(
+                "{} by {} at {}{}",
+                if approval.approved { "Approved" } else { "Rejected" },
+                approval.approver,
+                approval.at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"),
+                approval.reason.as_deref().map(|r| format!("\nReason: {}", r)).unwrap_or_default()
+            ),
+            None if app.session.last_run_status(task_id) == Some(&TaskStatus::AwaitingApproval) => {
+                "Awaiting approval │ a: Approve │ x: Reject".to_string()
+            }
+            None => "(gate: waits for approval once its dependencies are done)".to_string(),
+        }
+    } else if output_lines.is_empty() {
         "(no output yet)".to_string()
     } else {
         output_lines.join("\n")
@@ -308,12 +393,21 @@ fn render_output_panel(f: &mut Frame, app: &App, task_id: &str, area: Rect) {
     f.render_widget(output, area);
 }
 
-fn render_footer(f: &mut Frame, area: Rect) {
-    let help_text = "q: Quit │ k: Kill │ ↑↓: Select │ Enter: Terminal │ Tab: Cycle │ 1-3: Views";
-
-    let footer = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::DarkGray));
+fn render_footer(f: &mut Frame, app: &App, area: Rect) {
+    let footer = match &app.approval_prompt {
+        Some(prompt) => Paragraph::new(format!(
+            "{} {} │ Reason (optional): {}_ │ Enter: Confirm │ Esc: Cancel",
+            if prompt.approve { "Approve" } else { "Reject" },
+            prompt.task_id,
+            prompt.reason
+        ))
+        .style(Style::default().fg(Color::LightMagenta)),
+        None => Paragraph::new(
+            "q: Quit │ k: Kill │ a/x: Approve/Reject gate │ ↑↓: Select │ Enter: Terminal │ Tab: Cycle │ 1-3: Views",
+        )
+        .style(Style::default().fg(Color::DarkGray)),
+    }
+    .block(Block::default().borders(Borders::ALL));
 
     f.render_widget(footer, area);
 }
@@ -17,12 +17,31 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use crate::app::App;
 use ratatui::{
     backend::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders},
     Terminal,
 };
 use std::io;
 
+/// Bordered header block, titled with the graph reload banner if there is one
+pub(crate) fn header_block(app: &App) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL);
+    match app.reload_banner() {
+        Some(banner) => {
+            let color = if banner.is_error { Color::Red } else { Color::Green };
+            block.title(Span::styled(
+                format!(" ⟲ {} ", banner.message),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))
+        }
+        None => block,
+    }
+}
+
 /// Main TUI controller
 pub struct TUI {
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
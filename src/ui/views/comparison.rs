@@ -38,6 +38,26 @@ fn render_header(f: &mut Frame, _app: &App, area: ratatui::layout::Rect) {
 
 fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let task_ids = app.get_task_ids();
+    let graph = app.scheduler.graph();
+
+    // Matrix parameters get their own columns so variants line up
+    let mut matrix_keys: Vec<String> = Vec::new();
+    for task_id in &task_ids {
+        if let Some(values) = graph.get_task(task_id).and_then(|t| t.matrix_values.as_ref()) {
+            for key in values.keys() {
+                if !matrix_keys.contains(key) {
+                    matrix_keys.push(key.clone());
+                }
+            }
+        }
+    }
+    matrix_keys.sort();
+    let group_of = |task_id: &str| -> String {
+        graph
+            .get_task(task_id)
+            .and_then(|t| t.matrix_group.clone())
+            .unwrap_or_default()
+    };
 
     // Collect all metric keys across all tasks
     let mut all_metrics: Vec<String> = Vec::new();
@@ -55,10 +75,15 @@ fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect
     // Build header: Task | Status | Progress | ETA | <metric1> | <metric2> | ...
     let mut header_cells = vec![
         Cell::from("Task").style(Style::default().add_modifier(Modifier::BOLD)),
+    ];
+    for key in &matrix_keys {
+        header_cells.push(Cell::from(key.as_str()).style(Style::default().add_modifier(Modifier::BOLD)));
+    }
+    header_cells.extend([
         Cell::from("Status").style(Style::default().add_modifier(Modifier::BOLD)),
         Cell::from("Progress").style(Style::default().add_modifier(Modifier::BOLD)),
         Cell::from("ETA").style(Style::default().add_modifier(Modifier::BOLD)),
-    ];
+    ]);
     for metric_name in &all_metrics {
         header_cells.push(
             Cell::from(metric_name.as_str()).style(Style::default().add_modifier(Modifier::BOLD)),
@@ -66,14 +91,16 @@ fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect
     }
     let header_row = Row::new(header_cells).height(1);
 
-    // Find best values for highlighting
-    let mut best_values: std::collections::HashMap<String, (f64, bool)> = std::collections::HashMap::new();
+    // Find best values for highlighting (within each matrix group)
+    let mut best_values: std::collections::HashMap<(String, String), (f64, bool)> = std::collections::HashMap::new();
     for task_id in &task_ids {
         if let Some(metrics) = app.get_task_metrics(task_id) {
             for (key, value) in &metrics.metrics {
                 if let Some(v) = value.as_float() {
                     let lower_is_better = key == "loss" || key == "errors" || key == "warnings";
-                    let entry = best_values.entry(key.clone()).or_insert((v, lower_is_better));
+                    let entry = best_values
+                        .entry((group_of(task_id), key.clone()))
+                        .or_insert((v, lower_is_better));
                     if lower_is_better {
                         if v < entry.0 {
                             entry.0 = v;
@@ -90,14 +117,15 @@ fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect
     let rows: Vec<Row> = task_ids
         .iter()
         .filter(|id| {
-            // Only show tasks with some metrics or that are running
+            // Only show tasks with some metrics, that are running, or matrix variants
             app.get_task_metrics(id).is_some()
-                || app.scheduler.graph().get_task(id)
-                    .map(|t| t.status == GraphTaskStatus::InProgress)
+                || graph.get_task(id)
+                    .map(|t| t.status == GraphTaskStatus::InProgress || t.matrix_group.is_some())
                     .unwrap_or(false)
         })
         .map(|task_id| {
-            let task = app.scheduler.graph().get_task(task_id).unwrap();
+            let task = graph.get_task(task_id).unwrap();
+            let group = group_of(task_id);
             let metrics = app.get_task_metrics(task_id);
 
             let status_str = task.status.to_string();
@@ -106,14 +134,25 @@ fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect
                 .unwrap_or_else(|| "-".to_string());
             let eta_str = app.get_eta(task_id).unwrap_or_else(|| "-".to_string());
 
+            // Variants show their group name; the values are in the matrix columns
+            let name = task.matrix_group.as_deref().unwrap_or(task_id);
             let display_name = if app.workspace_mode {
-                task_id.split(':').nth(1).unwrap_or(task_id)
+                name.split_once(':').map(|(_, t)| t).unwrap_or(name)
             } else {
-                task_id
+                name
             };
 
-            let mut cells = vec![
-                Cell::from(display_name.to_string()),
+            let mut cells = vec![Cell::from(display_name.to_string())];
+            for key in &matrix_keys {
+                let value = task
+                    .matrix_values
+                    .as_ref()
+                    .and_then(|v| v.get(key))
+                    .map(|v| v.as_str())
+                    .unwrap_or("-");
+                cells.push(Cell::from(value.to_string()).style(Style::default().fg(Color::Cyan)));
+            }
+            cells.extend([
                 Cell::from(status_str).style(Style::default().fg(match task.status {
                     GraphTaskStatus::Done => Color::Green,
                     GraphTaskStatus::InProgress => Color::Yellow,
@@ -122,14 +161,14 @@ fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect
                 })),
                 Cell::from(progress_str),
                 Cell::from(eta_str),
-            ];
+            ]);
 
             for metric_name in &all_metrics {
                 let cell = if let Some(m) = metrics {
                     if let Some(value) = m.metrics.get(metric_name) {
                         let v_float = value.as_float();
                         let is_best = v_float.map(|v| {
-                            best_values.get(metric_name)
+                            best_values.get(&(group.clone(), metric_name.clone()))
                                 .map(|(best, _)| (v - best).abs() < 0.0001)
                                 .unwrap_or(false)
                         }).unwrap_or(false);
@@ -161,12 +200,15 @@ fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect
         .collect();
 
     // Column widths
-    let mut widths = vec![
-        Constraint::Min(15),     // Task
+    let mut widths = vec![Constraint::Min(15)]; // Task
+    for _ in &matrix_keys {
+        widths.push(Constraint::Length(10));
+    }
+    widths.extend([
         Constraint::Length(12),  // Status
         Constraint::Length(10),  // Progress
         Constraint::Length(10),  // ETA
-    ];
+    ]);
     for _ in &all_metrics {
         widths.push(Constraint::Length(12));
     }
@@ -176,7 +218,7 @@ fn render_comparison_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Metrics (* = best)"),
+                .title("Metrics (* = best, per matrix group)"),
         );
 
     f.render_widget(table, area);
@@ -75,6 +75,10 @@ fn render_dag(f: &mut Frame, app: &App, area: Rect) {
                     GraphTaskStatus::Failed => "✗",
                     GraphTaskStatus::Pending => "□",
                     GraphTaskStatus::Planned => "○",
+                    GraphTaskStatus::Skipped => "⊘",
+                    GraphTaskStatus::Cached => "≡",
+                    GraphTaskStatus::Ready => "●",
+                    GraphTaskStatus::AwaitingApproval => "⏸",
                 };
 
                 let status_color = match task.status {
@@ -83,21 +87,30 @@ fn render_dag(f: &mut Frame, app: &App, area: Rect) {
                     GraphTaskStatus::Failed => Color::Red,
                     GraphTaskStatus::Pending => Color::Gray,
                     GraphTaskStatus::Planned => Color::DarkGray,
+                    GraphTaskStatus::Skipped => Color::DarkGray,
+                    GraphTaskStatus::Cached => Color::Cyan,
+                    GraphTaskStatus::Ready => Color::LightGreen,
+                    GraphTaskStatus::AwaitingApproval => Color::LightMagenta,
                 };
 
-                // Show dependency arrows
-                let deps_str = task.depends_on.as_ref()
-                    .map(|deps| {
-                        if deps.is_empty() {
-                            String::new()
-                        } else {
-                            let short_deps: Vec<&str> = deps.iter()
-                                .map(|d| d.as_str())
-                                .collect();
-                            format!(" <── {}", short_deps.join(", "))
-                        }
-                    })
-                    .unwrap_or_default();
+                // Show dependency arrows; edges into other projects stand out
+                let project = app.get_project_name(task_id);
+                let (local_deps, cross_deps): (Vec<&str>, Vec<&str>) = task
+                    .depends_on
+                    .iter()
+                    .flatten()
+                    .map(|d| d.as_str())
+                    .partition(|d| project.is_none() || app.get_project_name(d) == project);
+                let deps_str = if local_deps.is_empty() {
+                    String::new()
+                } else {
+                    format!(" <── {}", local_deps.join(", "))
+                };
+                let cross_str = if cross_deps.is_empty() {
+                    String::new()
+                } else {
+                    format!(" <══ {}", cross_deps.join(", "))
+                };
 
                 let arrow = if depth > 0 { "├─ " } else { "" };
 
@@ -114,6 +127,7 @@ fn render_dag(f: &mut Frame, app: &App, area: Rect) {
                             .add_modifier(Modifier::BOLD),
                     ),
                     Span::styled(deps_str, Style::default().fg(Color::DarkGray)),
+                    Span::styled(cross_str, Style::default().fg(Color::Magenta)),
                 ]);
 
                 items.push(ListItem::new(line));
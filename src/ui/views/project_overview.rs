@@ -11,6 +11,7 @@
 
 use crate::agents::AgentRuntimeStatus;
 use crate::app::App;
+use crate::ui::header_block;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -81,7 +82,7 @@ fn render_header(f: &mut Frame, app: &App, area: Rect) {
     );
     
     let header = Paragraph::new(title)
-        .block(Block::default().borders(Borders::ALL))
+        .block(header_block(app))
         .style(Style::default().fg(Color::Cyan));
     
     f.render_widget(header, area);
@@ -173,6 +174,15 @@ fn render_project_list(f: &mut Frame, app: &App, area: Rect) {
                 format!(" {:>3}%", progress_pct),
                 Style::default().fg(if progress_pct == 100 { Color::Green } else { Color::Yellow }),
             ),
+            // Projects it waits on
+            Span::styled(
+                if summary.depends_on_projects.is_empty() {
+                    String::new()
+                } else {
+                    format!("  <══ {}", summary.depends_on_projects.join(", "))
+                },
+                Style::default().fg(Color::Magenta),
+            ),
         ]);
         
         // Recent event (second line)
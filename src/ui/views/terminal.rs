@@ -66,6 +66,10 @@ pub fn render_terminal_view(f: &mut Frame, app: &App) {
         GraphTaskStatus::Failed => "✗",
         GraphTaskStatus::Pending => "□",
         GraphTaskStatus::Planned => "○",
+        GraphTaskStatus::Skipped => "⊘",
+        GraphTaskStatus::Cached => "≡",
+        GraphTaskStatus::Ready => "●",
+        GraphTaskStatus::AwaitingApproval => "⏸",
     };
 
     let status_color = match task.status {
@@ -1,11 +1,41 @@
 //! Multi-project workspace management
+//!
+//! Projects are directories with a `.gid/graph.yml` (or `.toml`/`.json`).
+//! They are found recursively below the workspace root, or listed in a
+//! `gidterm-workspace.yml` manifest:
+//!
+//! ```yaml
+//! projects:
+//!   - services/*                 # directories or globs, relative to the root
+//!   - path: apps/web
+//!     env: { NODE_ENV: development }
+//!     port: 3100                 # preferred $PORT
+//! exclude: [services/legacy-*]
+//! max_depth: 4                   # recursive discovery, when `projects` is empty
+//! ignore: [fixtures]             # more directory names discovery skips
+//! ```
+//!
+//! A project is named by its path relative to the root, e.g. `services/api`.
 
 use anyhow::Result;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::core::Graph;
+use crate::core::{
+    rename_output_sources, split_project_ref, Graph, PolicyKind, SchedulingConfig, ValidationIssue,
+    ValidationReport,
+};
+
+/// Manifest file at the workspace root
+pub const MANIFEST_FILE: &str = "gidterm-workspace.yml";
+
+/// How deep recursive discovery looks (direct subdirectories are depth 1)
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Directory names recursive discovery never enters
+pub const DEFAULT_IGNORE: &[&str] = &["node_modules", "target", ".git", ".gid", ".gidterm"];
 
 /// A workspace containing multiple projects
 #[derive(Debug, Clone)]
@@ -19,43 +49,187 @@ pub struct Workspace {
 pub struct Project {
     pub name: String,
     pub path: PathBuf,
+    /// Graph file (.gid/graph.yml, .toml or .json)
+    pub graph_path: PathBuf,
     pub graph: Graph,
+    /// Environment for every task of the project (a task's own `env` wins)
+    pub env: HashMap<String, String>,
+    /// Preferred port for `$PORT`
+    pub port: Option<u16>,
+}
+
+/// `gidterm-workspace.yml`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WorkspaceManifest {
+    /// Project directories or globs; discovered recursively if empty
+    #[serde(default)]
+    pub projects: Vec<ProjectEntry>,
+    /// Globs of project paths to leave out
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// How deep recursive discovery looks (default 3)
+    pub max_depth: Option<usize>,
+    /// Directory names (or globs) recursive discovery skips, on top of the defaults
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+/// One entry of the manifest's `projects`: a path or glob, or a table with
+/// `path`, `env` and `port`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "RawProjectEntry")]
+pub struct ProjectEntry {
+    pub path: String,
+    pub env: HashMap<String, String>,
+    pub port: Option<u16>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawProjectEntry {
+    Path(String),
+    Config {
+        path: String,
+        #[serde(default)]
+        env: HashMap<String, serde_yaml::Value>,
+        port: Option<u16>,
+    },
+}
+
+impl From<RawProjectEntry> for ProjectEntry {
+    fn from(raw: RawProjectEntry) -> Self {
+        match raw {
+            RawProjectEntry::Path(path) => Self {
+                path,
+                ..Default::default()
+            },
+            RawProjectEntry::Config { path, env, port } => Self {
+                path,
+                // Values may be written as numbers or booleans
+                env: env
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let v = match v {
+                            serde_yaml::Value::String(s) => s,
+                            other => serde_yaml::to_string(&other)
+                                .unwrap_or_default()
+                                .trim_end()
+                                .to_string(),
+                        };
+                        (k, v)
+                    })
+                    .collect(),
+                port,
+            },
+        }
+    }
+}
+
+/// A project directory with its env and preferred port
+type ProjectDir = (PathBuf, HashMap<String, String>, Option<u16>);
+
+impl WorkspaceManifest {
+    /// Read a manifest file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        serde_yaml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid workspace manifest {}: {}", path.display(), e))
+    }
+
+    /// Project directories below `root` with their env and preferred port
+    fn project_dirs(&self, root: &Path) -> Result<Vec<ProjectDir>> {
+        let exclude = compile_globs(&self.exclude)?;
+        let excluded = |dir: &Path| {
+            let name = project_name(root, dir);
+            exclude.iter().any(|p| p.matches(&name))
+        };
+
+        if self.projects.is_empty() {
+            let mut ignore = compile_globs(&self.ignore)?;
+            ignore.extend(compile_globs(DEFAULT_IGNORE)?);
+            let mut dirs = Vec::new();
+            find_projects(root, 1, self.max_depth.unwrap_or(DEFAULT_MAX_DEPTH), &ignore, &mut dirs)?;
+            return Ok(dirs
+                .into_iter()
+                .filter(|dir| !excluded(dir))
+                .map(|dir| (dir, HashMap::new(), None))
+                .collect());
+        }
+
+        let mut dirs = Vec::new();
+        for entry in &self.projects {
+            let pattern = &entry.path;
+            let full = format!(
+                "{}/{}",
+                glob::Pattern::escape(&root.to_string_lossy()).trim_end_matches('/'),
+                pattern.trim_end_matches('/')
+            );
+            let matches: Vec<PathBuf> = glob::glob(&full)
+                .map_err(|e| anyhow::anyhow!("Invalid project pattern '{}': {}", pattern, e))?
+                .flatten()
+                .filter(|path| path.is_dir())
+                .collect();
+            if matches.is_empty() {
+                log::warn!("{}: no directory matches '{}'", MANIFEST_FILE, pattern);
+            }
+            for dir in matches {
+                if excluded(&dir) || dirs.iter().any(|(d, _, _)| *d == dir) {
+                    continue;
+                }
+                if Graph::find_file(&dir.join(".gid"), "graph").is_none() {
+                    log::warn!("{}: {} has no .gid/graph.yml", MANIFEST_FILE, dir.display());
+                    continue;
+                }
+                dirs.push((dir, entry.env.clone(), entry.port));
+            }
+        }
+        Ok(dirs)
+    }
 }
 
 impl Workspace {
     /// Discover and load all projects in a directory
+    ///
+    /// Uses `gidterm-workspace.yml` if the directory has one, otherwise
+    /// searches subdirectories recursively (up to `DEFAULT_MAX_DEPTH`).
     pub fn discover(root: &Path) -> Result<Self> {
+        let manifest_path = root.join(MANIFEST_FILE);
+        let manifest = if manifest_path.exists() {
+            log::info!("Using workspace manifest {}", manifest_path.display());
+            WorkspaceManifest::load(&manifest_path)?
+        } else {
+            WorkspaceManifest::default()
+        };
+        Self::from_manifest(root, &manifest)
+    }
+
+    /// Load the projects a manifest lists (or discovers)
+    pub fn from_manifest(root: &Path, manifest: &WorkspaceManifest) -> Result<Self> {
         let mut projects = HashMap::new();
 
-        // Walk subdirectories looking for .gid/graph.yml
-        for entry in fs::read_dir(root)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                let graph_path = path.join(".gid/graph.yml");
-                if graph_path.exists() {
-                    let name = path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unknown")
-                        .to_string();
-
-                    match Graph::from_file(&graph_path) {
-                        Ok(graph) => {
-                            projects.insert(
-                                name.clone(),
-                                Project {
-                                    name,
-                                    path,
-                                    graph,
-                                },
-                            );
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to load graph from {:?}: {}", graph_path, e);
-                        }
-                    }
+        for (path, env, port) in manifest.project_dirs(root)? {
+            let Some(graph_path) = Graph::find_file(&path.join(".gid"), "graph") else {
+                continue;
+            };
+            let name = project_name(root, &path);
+
+            match Graph::from_file(&graph_path) {
+                Ok(graph) => {
+                    projects.insert(
+                        name.clone(),
+                        Project {
+                            name,
+                            path,
+                            graph_path,
+                            graph,
+                            env,
+                            port,
+                        },
+                    );
+                }
+                Err(e) => {
+                    log::warn!("Failed to load graph from {:?}: {}", graph_path, e);
                 }
             }
         }
@@ -74,21 +248,32 @@ impl Workspace {
     pub fn load_projects(root: &Path, project_names: &[String]) -> Result<Self> {
         let mut projects = HashMap::new();
 
+        // Settings from the manifest, if there is one
+        let manifest_path = root.join(MANIFEST_FILE);
+        let mut settings = HashMap::new();
+        if manifest_path.exists() {
+            for (path, env, port) in WorkspaceManifest::load(&manifest_path)?.project_dirs(root)? {
+                settings.insert(project_name(root, &path), (env, port));
+            }
+        }
+
         for name in project_names {
             let project_path = root.join(name);
-            let graph_path = project_path.join(".gid/graph.yml");
-
-            if !graph_path.exists() {
+            let Some(graph_path) = Graph::find_file(&project_path.join(".gid"), "graph") else {
                 anyhow::bail!("No .gid/graph.yml found in project: {}", name);
-            }
+            };
 
             let graph = Graph::from_file(&graph_path)?;
+            let (env, port) = settings.remove(name.trim_end_matches('/')).unwrap_or_default();
             projects.insert(
                 name.clone(),
                 Project {
                     name: name.clone(),
                     path: project_path,
+                    graph_path,
                     graph,
+                    env,
+                    port,
                 },
             );
         }
@@ -101,6 +286,10 @@ impl Workspace {
 
     /// Create a unified graph with namespaced task IDs
     /// Task IDs become: "project:task_id"
+    ///
+    /// Dependencies on other projects (`backend:build` or `@backend/build`)
+    /// point at that project's task; everything else stays in the project.
+    /// Scheduling comes from [`Workspace::scheduling`].
     pub fn to_unified_graph(&self) -> Graph {
         let mut unified_tasks = HashMap::new();
         let mut unified_nodes = HashMap::new();
@@ -110,15 +299,31 @@ impl Workspace {
             for (task_id, task) in &project.graph.tasks {
                 let namespaced_id = format!("{}:{}", project_name, task_id);
                 
-                // Clone and update dependencies to be namespaced too
+                // Clone and update dependencies to be namespaced too;
+                // tasks run inside their own project directory
                 let mut namespaced_task = task.clone();
+                namespaced_task.root = Some(project.path.clone());
+                if !project.env.is_empty() {
+                    let mut env = project.env.clone();
+                    env.extend(task.env.clone().unwrap_or_default());
+                    namespaced_task.env = Some(env);
+                }
+                if let Some(group) = &task.matrix_group {
+                    namespaced_task.matrix_group = Some(format!("{}:{}", project_name, group));
+                }
                 if let Some(deps) = &task.depends_on {
                     namespaced_task.depends_on = Some(
                         deps.iter()
-                            .map(|dep| format!("{}:{}", project_name, dep))
+                            .map(|dep| qualify_dependency(project_name, &project.graph, dep))
                             .collect(),
                     );
                 }
+                // `${tasks.<id>.outputs.*}` refers to tasks the same way
+                namespaced_task.rewrite_fields(|text| {
+                    rename_output_sources(text, |source| {
+                        Some(qualify_dependency(project_name, &project.graph, source))
+                    })
+                });
 
                 unified_tasks.insert(namespaced_id, namespaced_task);
             }
@@ -131,7 +336,7 @@ impl Workspace {
                 if let Some(deps) = &node.depends_on {
                     namespaced_node.depends_on = Some(
                         deps.iter()
-                            .map(|dep| format!("{}:{}", project_name, dep))
+                            .map(|dep| qualify_dependency(project_name, &project.graph, dep))
                             .collect(),
                     );
                 }
@@ -141,6 +346,9 @@ impl Workspace {
         }
 
         Graph {
+            include: Vec::new(),
+            vars: None,
+            scheduling: self.scheduling(),
             metadata: Some(crate::core::Metadata {
                 project: "workspace".to_string(),
                 version: Some("1.0.0".to_string()),
@@ -151,6 +359,71 @@ impl Workspace {
         }
     }
 
+    /// Scheduling of the whole workspace, merged from the projects' `scheduling:`
+    ///
+    /// The smallest `max_parallel` wins and caps all projects together. A
+    /// policy is used only if every project that sets one agrees; otherwise
+    /// the default applies and [`Workspace::validate`] warns.
+    pub fn scheduling(&self) -> Option<SchedulingConfig> {
+        let configs: Vec<SchedulingConfig> = self.projects.values().filter_map(|p| p.graph.scheduling).collect();
+        if configs.is_empty() {
+            return None;
+        }
+        let policies = self.scheduling_policies();
+        let policy = policies.first().map(|(_, policy)| *policy);
+        Some(SchedulingConfig {
+            policy: policy.filter(|first| policies.iter().all(|(_, policy)| policy == first)),
+            max_parallel: configs.iter().filter_map(|c| c.max_parallel).min(),
+        })
+    }
+
+    /// Policies set by projects, sorted by project name
+    fn scheduling_policies(&self) -> Vec<(String, PolicyKind)> {
+        let mut policies: Vec<(String, PolicyKind)> = self
+            .projects
+            .iter()
+            .filter_map(|(name, project)| {
+                let policy = project.graph.scheduling?.policy?;
+                Some((name.clone(), policy))
+            })
+            .collect();
+        policies.sort_by(|a, b| a.0.cmp(&b.0));
+        policies
+    }
+
+    /// Validate the unified graph, including dependencies across projects
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = self.to_unified_graph().validate();
+        let policies = self.scheduling_policies();
+        if policies.iter().any(|(_, policy)| *policy != policies[0].1) {
+            report.issues.push(ValidationIssue::ConflictingPolicies { policies });
+        }
+        // Every project is loaded, so a dependency that is still missing is unknown
+        for issue in &mut report.issues {
+            if let ValidationIssue::ExternalDependency { task, dependency } = issue {
+                *issue = ValidationIssue::UnknownDependency {
+                    task: std::mem::take(task),
+                    dependency: std::mem::take(dependency),
+                };
+            }
+        }
+        report
+    }
+
+    /// Fail on validation errors, logging warnings
+    pub fn ensure_valid(&self) -> Result<()> {
+        let report = self.validate();
+        for warning in report.warnings() {
+            log::warn!("workspace: {}", warning);
+        }
+        let errors = report.errors();
+        if !errors.is_empty() {
+            let details: Vec<String> = errors.iter().map(|e| format!("  - {}", e)).collect();
+            anyhow::bail!("Invalid workspace {}:\n{}", self.root.display(), details.join("\n"));
+        }
+        Ok(())
+    }
+
     /// Get project count
     pub fn project_count(&self) -> usize {
         self.projects.len()
@@ -177,13 +450,302 @@ impl Workspace {
     }
 }
 
+/// Project name: its path relative to the workspace root, `/`-separated
+fn project_name(root: &Path, dir: &Path) -> String {
+    let relative = dir.strip_prefix(root).unwrap_or(dir);
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if parts.is_empty() {
+        "unknown".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+fn compile_globs<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<glob::Pattern>> {
+    patterns
+        .iter()
+        .map(|p| {
+            glob::Pattern::new(p.as_ref().trim_end_matches('/'))
+                .map_err(|e| anyhow::anyhow!("Invalid pattern '{}': {}", p.as_ref(), e))
+        })
+        .collect()
+}
+
+/// Collect project directories below `dir`, sorted; projects are not searched
+/// for nested projects
+fn find_projects(
+    dir: &Path,
+    depth: usize,
+    max_depth: usize,
+    ignore: &[glob::Pattern],
+    found: &mut Vec<PathBuf>,
+) -> Result<()> {
+    if depth > max_depth {
+        return Ok(());
+    }
+    let mut subdirs: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            !ignore.iter().any(|p| p.matches(&name))
+        })
+        .collect();
+    subdirs.sort();
+
+    for path in subdirs {
+        if Graph::find_file(&path.join(".gid"), "graph").is_some() {
+            found.push(path);
+        } else {
+            find_projects(&path, depth + 1, max_depth, ignore, found)?;
+        }
+    }
+    Ok(())
+}
+
+/// Namespaced ID of a dependency of a task in `project`
+///
+/// Local task IDs win over cross-project references, since IDs like
+/// `docker:build` are valid task names.
+fn qualify_dependency(project: &str, graph: &Graph, dep: &str) -> String {
+    if graph.tasks.contains_key(dep) || graph.nodes.contains_key(dep) {
+        return format!("{}:{}", project, dep);
+    }
+    match split_project_ref(dep) {
+        Some((other, task)) => format!("{}:{}", other, task),
+        None => format!("{}:{}", project, dep),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    
+    use super::*;
 
     #[test]
     fn test_workspace_creation() {
         // Test that workspace can be created
         // (Actual discovery would need real filesystem)
     }
+
+    #[test]
+    fn test_unified_graph_keeps_project_root() {
+        let root = tempfile::TempDir::new().unwrap();
+        let project = root.path().join("frontend");
+        fs::create_dir_all(project.join(".gid")).unwrap();
+        fs::create_dir_all(project.join("web")).unwrap();
+        fs::write(
+            project.join(".gid/graph.yml"),
+            r#"
+tasks:
+  install: { description: Install, command: npm install }
+  dev: { description: Dev server, command: npm run dev, cwd: web, depends_on: [install] }
+"#,
+        )
+        .unwrap();
+
+        let workspace = Workspace::discover(root.path()).unwrap();
+        let graph = workspace.to_unified_graph();
+
+        let install = graph.get_task("frontend:install").unwrap();
+        assert_eq!(install.spawn_options().unwrap().cwd, Some(project.clone()));
+
+        let dev = graph.get_task("frontend:dev").unwrap();
+        assert_eq!(dev.spawn_options().unwrap().cwd, Some(project.join("web")));
+        assert_eq!(dev.depends_on, Some(vec!["frontend:install".to_string()]));
+    }
+
+    #[test]
+    fn test_unified_graph_qualifies_output_references() {
+        let root = tempfile::TempDir::new().unwrap();
+        let project = root.path().join("app");
+        fs::create_dir_all(project.join(".gid")).unwrap();
+        fs::write(
+            project.join(".gid/graph.yml"),
+            r#"
+tasks:
+  build:
+    description: Build
+    command: ./build.sh
+    outputs_capture: { version: "v(\\S+)" }
+  deploy:
+    description: Deploy
+    command: "./deploy ${tasks.build.outputs.version}"
+    depends_on: [build]
+"#,
+        )
+        .unwrap();
+
+        let workspace = Workspace::discover(root.path()).unwrap();
+        let graph = workspace.to_unified_graph();
+        assert_eq!(
+            graph.tasks["app:deploy"].command.as_deref(),
+            Some("./deploy ${tasks.app:build.outputs.version}")
+        );
+        let report = workspace.validate();
+        assert!(report.is_ok(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_cross_project_dependencies() {
+        let root = tempfile::TempDir::new().unwrap();
+        for (name, graph) in [
+            ("backend", "tasks:\n  build: { description: Build, command: cargo build }\n"),
+            (
+                "frontend",
+                r#"
+tasks:
+  "docker:build": { description: Image, command: docker build . }
+  e2e:
+    description: E2E
+    command: npm run e2e
+    depends_on: ["backend:build", "docker:build", "@backend/build"]
+"#,
+            ),
+        ] {
+            fs::create_dir_all(root.path().join(name).join(".gid")).unwrap();
+            fs::write(root.path().join(name).join(".gid/graph.yml"), graph).unwrap();
+        }
+
+        let mut workspace = Workspace::discover(root.path()).unwrap();
+        workspace.ensure_valid().unwrap();
+        let graph = workspace.to_unified_graph();
+        assert_eq!(
+            graph.get_task("frontend:e2e").unwrap().depends_on,
+            Some(vec![
+                "backend:build".to_string(),
+                "frontend:docker:build".to_string(),
+                "backend:build".to_string(),
+            ])
+        );
+
+        // A reference to a task no project has is an error in the workspace
+        let frontend = workspace.projects.get_mut("frontend").unwrap();
+        frontend.graph.tasks.get_mut("e2e").unwrap().depends_on = Some(vec!["backend:deploy".into()]);
+        assert_eq!(
+            workspace.validate().errors(),
+            vec![&ValidationIssue::UnknownDependency {
+                task: "frontend:e2e".into(),
+                dependency: "backend:deploy".into()
+            }]
+        );
+        assert!(workspace.ensure_valid().is_err());
+    }
+
+    #[test]
+    fn test_unified_graph_merges_scheduling() {
+        let root = tempfile::TempDir::new().unwrap();
+        for (name, scheduling) in [
+            ("api", "scheduling: { policy: critical-path, max_parallel: 4 }\n"),
+            ("web", "scheduling: { max_parallel: 2 }\n"),
+            ("docs", ""),
+        ] {
+            fs::create_dir_all(root.path().join(name).join(".gid")).unwrap();
+            fs::write(
+                root.path().join(name).join(".gid/graph.yml"),
+                format!("{}tasks:\n  build: {{ description: Build, command: make }}\n", scheduling),
+            )
+            .unwrap();
+        }
+
+        // The smallest limit wins; the only policy set is used
+        let mut workspace = Workspace::discover(root.path()).unwrap();
+        let scheduling = workspace.to_unified_graph().scheduling.unwrap();
+        assert_eq!(scheduling.policy, Some(PolicyKind::CriticalPath));
+        assert_eq!(scheduling.max_parallel.map(|n| n.get()), Some(2));
+        assert!(workspace.validate().warnings().is_empty());
+
+        // Projects that disagree fall back to the default policy, with a warning
+        let web = workspace.projects.get_mut("web").unwrap();
+        web.graph.scheduling.as_mut().unwrap().policy = Some(PolicyKind::FairShare);
+        assert_eq!(workspace.scheduling().unwrap().policy, None);
+        assert_eq!(
+            workspace.validate().warnings(),
+            vec![&ValidationIssue::ConflictingPolicies {
+                policies: vec![
+                    ("api".to_string(), PolicyKind::CriticalPath),
+                    ("web".to_string(), PolicyKind::FairShare),
+                ]
+            }]
+        );
+    }
+
+    fn write_project(root: &Path, name: &str) {
+        fs::create_dir_all(root.join(name).join(".gid")).unwrap();
+        fs::write(
+            root.join(name).join(".gid/graph.yml"),
+            "tasks:\n  build: { description: Build, command: make }\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_recursive_discovery() {
+        let root = tempfile::TempDir::new().unwrap();
+        for name in [
+            "services/api",
+            "services/worker",
+            "packages/ui",
+            "packages/ui/examples/demo",
+            "node_modules/dep",
+            "a/b/c/too-deep",
+        ] {
+            write_project(root.path(), name);
+        }
+
+        let workspace = Workspace::discover(root.path()).unwrap();
+        assert_eq!(
+            workspace.project_names(),
+            vec!["packages/ui", "services/api", "services/worker"]
+        );
+        assert!(workspace
+            .to_unified_graph()
+            .get_task("services/api:build")
+            .is_some());
+
+        let manifest = WorkspaceManifest {
+            max_depth: Some(4),
+            ignore: vec!["services".into()],
+            ..Default::default()
+        };
+        let workspace = Workspace::from_manifest(root.path(), &manifest).unwrap();
+        assert_eq!(workspace.project_names(), vec!["a/b/c/too-deep", "packages/ui"]);
+    }
+
+    #[test]
+    fn test_manifest_projects() {
+        let root = tempfile::TempDir::new().unwrap();
+        for name in ["services/api", "services/legacy-billing", "apps/web", "tools/lint"] {
+            write_project(root.path(), name);
+        }
+        fs::write(
+            root.path().join(MANIFEST_FILE),
+            r#"
+projects:
+  - services/*
+  - path: apps/web
+    env: { NODE_ENV: development, PORT_OFFSET: 1 }
+    port: 3100
+exclude: [services/legacy-*]
+"#,
+        )
+        .unwrap();
+
+        let workspace = Workspace::discover(root.path()).unwrap();
+        assert_eq!(workspace.project_names(), vec!["apps/web", "services/api"]);
+
+        let web = &workspace.projects["apps/web"];
+        assert_eq!(web.port, Some(3100));
+        let graph = workspace.to_unified_graph();
+        let env = graph.get_task("apps/web:build").unwrap().env.clone().unwrap();
+        assert_eq!(env.get("NODE_ENV").map(String::as_str), Some("development"));
+        assert_eq!(env.get("PORT_OFFSET").map(String::as_str), Some("1"));
+        assert!(graph.get_task("services/api:build").unwrap().env.is_none());
+
+        let selected = Workspace::load_projects(root.path(), &["apps/web".to_string()]).unwrap();
+        assert_eq!(selected.projects["apps/web"].port, Some(3100));
+    }
 }
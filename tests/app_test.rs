@@ -0,0 +1,142 @@
+use gidterm::ai::control::{ControlCommand, ControlResponse};
+use gidterm::ai::{ControlMode, GidEvent};
+use gidterm::{App, Graph, GraphTaskStatus};
+use std::sync::LazyLock;
+use tempfile::TempDir;
+
+/// Sessions are saved under the working directory, so the tests run in a
+/// scratch directory instead of the repository
+static WORKDIR: LazyLock<TempDir> = LazyLock::new(|| {
+    let dir = TempDir::new().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+    dir
+});
+
+fn app(yaml: &str) -> App {
+    LazyLock::force(&WORKDIR);
+    let graph: Graph = serde_yaml::from_str(yaml).unwrap();
+    App::new(graph)
+}
+
+fn command(json: &str) -> ControlCommand {
+    serde_json::from_str(json).unwrap()
+}
+
+#[tokio::test]
+async fn test_control_commands_decide_gates() {
+    let mut app = app(
+        r#"
+tasks:
+  approve-deploy: { description: Approve deploy, type: gate }
+  approve-prod: { description: Approve prod, type: gate }
+  deploy: { description: Deploy, command: "make deploy", depends_on: [approve-deploy] }
+  prod: { description: Prod, command: "make prod", depends_on: [approve-prod] }
+"#,
+    );
+    let mut events = app.events.subscribe();
+
+    app.start_ready_tasks().await.unwrap();
+    let mut waiting = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let GidEvent::GateAwaitingApproval { task_id } = event {
+            waiting.push(task_id);
+        }
+    }
+    assert_eq!(waiting, vec!["approve-deploy", "approve-prod"]);
+
+    let response = app.handle_command(command(r#"{"action":"approve_gate","task_id":"approve-deploy"}"#), ControlMode::Mcp);
+    assert!(matches!(response, ControlResponse::Ok { .. }), "{:?}", response);
+    match events.try_recv().unwrap() {
+        GidEvent::GateDecided { task_id, approved, approver, reason } => {
+            assert_eq!(task_id, "approve-deploy");
+            assert!(approved);
+            assert_eq!(approver, "mcp");
+            assert_eq!(reason, None);
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+
+    let response = app.handle_command(
+        command(r#"{"action":"reject_gate","task_id":"approve-prod","approver":"release-bot","reason":"freeze"}"#),
+        ControlMode::Agent,
+    );
+    assert!(matches!(response, ControlResponse::Ok { .. }), "{:?}", response);
+    match events.try_recv().unwrap() {
+        GidEvent::GateDecided { approved, approver, reason, .. } => {
+            assert!(!approved);
+            assert_eq!(approver, "release-bot");
+            assert_eq!(reason.as_deref(), Some("freeze"));
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+
+    let graph = app.scheduler.graph();
+    assert_eq!(graph.get_task("approve-deploy").unwrap().status, GraphTaskStatus::Done);
+    assert_eq!(graph.get_task("approve-prod").unwrap().status, GraphTaskStatus::Failed);
+    assert_eq!(graph.get_ready_tasks(), vec!["deploy"]);
+    assert_eq!(graph.get_task("prod").unwrap().status, GraphTaskStatus::Skipped);
+    let approval = app.session.tasks["approve-prod"].runs.last().unwrap().approval.clone().unwrap();
+    assert_eq!(approval.approver, "release-bot");
+
+    // Gates that are not waiting cannot be decided again
+    let response = app.handle_command(command(r#"{"action":"approve_gate","task_id":"approve-prod"}"#), ControlMode::Mcp);
+    assert!(matches!(response, ControlResponse::Error { .. }), "{:?}", response);
+
+    let ControlResponse::Ok { data: Some(state) } =
+        app.handle_command(ControlCommand::GetState, ControlMode::Mcp)
+    else {
+        panic!("no state");
+    };
+    assert_eq!(state["total_count"], 4);
+    assert_eq!(state["done_count"], 1);
+    assert_eq!(state["tasks"][0]["id"], "approve-deploy");
+    assert_eq!(state["tasks"][0]["status"], "done");
+}
+
+#[test]
+fn test_resume_reruns_only_unfinished_tasks() {
+    let mut app = app(
+        r#"
+tasks:
+  build: { description: Build, command: make }
+  lint: { description: Lint, command: make lint }
+  docs: { description: Docs, command: make docs }
+  test: { description: Test, command: make test, depends_on: [build, lint] }
+  report: { description: Report failure, command: ./report, depends_on: [build], on: failure }
+"#,
+    );
+
+    // build succeeded, lint failed and test was skipped because of it;
+    // docs never started
+    let mut session = gidterm::Session::new("unknown".to_string());
+    session.start_task("build".to_string());
+    session.add_output("build", "built".to_string());
+    session.end_task("build", gidterm::TaskStatus::Done, Some(0));
+    session.start_task("lint".to_string());
+    session.end_task("lint", gidterm::TaskStatus::Failed, Some(1));
+    session.skip_task("test".to_string());
+    session.skip_task("report".to_string());
+
+    let (restored, requeued) = app.resume(session).unwrap();
+    assert_eq!(restored, vec!["build"]);
+    assert_eq!(requeued, vec!["lint", "report", "test"]);
+    assert_eq!(app.get_task_output("build", 10), vec!["built"]);
+
+    // build stays done, so its failure handler is skipped again
+    let status = |app: &App, id: &str| app.scheduler.graph().get_task(id).unwrap().status.clone();
+    assert_eq!(status(&app, "build"), GraphTaskStatus::Done);
+    assert_eq!(status(&app, "report"), GraphTaskStatus::Skipped);
+    assert_eq!(status(&app, "lint"), GraphTaskStatus::Pending);
+
+    assert_eq!(app.scheduler.schedule_next(), vec!["docs", "lint"]);
+    for id in ["docs", "lint"] {
+        app.scheduler.mark_started(id).unwrap();
+        app.scheduler.mark_done(id).unwrap();
+    }
+    assert_eq!(app.scheduler.schedule_next(), vec!["test"]);
+    app.scheduler.mark_started("test").unwrap();
+    app.scheduler.mark_done("test").unwrap();
+    assert!(app.scheduler.schedule_next().is_empty());
+    assert!(app.scheduler.all_done());
+    assert_eq!(app.session.tasks["build"].runs.len(), 1);
+}
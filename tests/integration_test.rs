@@ -74,6 +74,30 @@ fn test_graph_update_nonexistent_task() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_graph_from_file_rejects_invalid_graph() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("graph.yml");
+    std::fs::write(
+        &path,
+        r#"
+tasks:
+  a: { description: A, command: "true", depends_on: [b] }
+  b: { description: B, command: "true", depends_on: [a] }
+  c: { description: C, command: "true", depends_on: [typo] }
+"#,
+    )
+    .unwrap();
+
+    let err = Graph::from_file(&path).unwrap_err().to_string();
+    assert!(err.contains("dependency cycle: a -> b -> a"));
+    assert!(err.contains("unknown task 'typo'"));
+
+    // The unchecked loader still parses it so `gidterm validate` can report
+    let graph = Graph::from_file_unchecked(&path).unwrap();
+    assert_eq!(graph.validate().errors().len(), 2);
+}
+
 // === Scheduler Tests ===
 
 #[test]
@@ -136,7 +160,7 @@ fn test_scheduler_full_progression() {
 }
 
 #[test]
-fn test_scheduler_failed_task_blocks_dependents() {
+fn test_scheduler_failed_task_skips_dependents() {
     let graph = Graph::from_file(Path::new("test-gid-integration.yml")).unwrap();
     let mut scheduler = Scheduler::new(graph);
 
@@ -147,8 +171,356 @@ fn test_scheduler_failed_task_blocks_dependents() {
     let ready = scheduler.schedule_next();
     assert!(ready.is_empty());
 
-    // But all_done should be false since pending tasks remain
+    // Every transitive dependent is skipped, so the run is over
+    let mut skipped = scheduler.take_skipped();
+    skipped.sort();
+    assert_eq!(skipped, vec!["final", "parallel1", "parallel2", "world"]);
+    assert_eq!(
+        scheduler.graph().get_task("final").unwrap().status,
+        GraphTaskStatus::Skipped
+    );
+    assert!(scheduler.all_done());
+}
+
+#[test]
+fn test_scheduler_cached_task_unblocks_dependents() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  build: { description: Build, command: "make", inputs: ["src/*.c"], outputs: [app] }
+  test: { description: Test, command: "./app --test", depends_on: [build] }
+"#,
+    )
+    .unwrap();
+    let mut scheduler = Scheduler::new(graph);
+
+    assert_eq!(scheduler.schedule_next(), vec!["build"]);
+    scheduler.mark_cached("build").unwrap();
+    assert_eq!(
+        scheduler.graph().get_task("build").unwrap().status,
+        GraphTaskStatus::Cached
+    );
+    assert_eq!(scheduler.schedule_next(), vec!["test"]);
+    scheduler.mark_started("test").unwrap();
+    scheduler.mark_done("test").unwrap();
+    assert!(scheduler.all_done());
+}
+
+#[test]
+fn test_scheduler_requeue_resets_dependents() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  build: { description: Build, command: "make" }
+  test: { description: Test, command: "make test", depends_on: [build] }
+  deploy: { description: Deploy, command: "make deploy", depends_on: [test] }
+  lint: { description: Lint, command: "make lint" }
+"#,
+    )
+    .unwrap();
+    let mut scheduler = Scheduler::new(graph);
+
+    for id in ["build", "lint"] {
+        scheduler.mark_started(id).unwrap();
+        scheduler.mark_done(id).unwrap();
+    }
+    scheduler.mark_started("test").unwrap();
+
+    // deploy has not started yet, lint does not depend on build
+    assert_eq!(scheduler.requeue("build").unwrap(), vec!["build", "test"]);
+    assert_eq!(
+        scheduler.graph().get_task("test").unwrap().status,
+        GraphTaskStatus::Pending
+    );
+    assert_eq!(scheduler.schedule_next(), vec!["build"]);
+    assert!(scheduler.get_running().is_empty());
+    assert!(scheduler.requeue("missing").is_err());
+}
+
+#[test]
+fn test_scheduler_max_parallel_follows_priority() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+scheduling: { max_parallel: 2 }
+tasks:
+  docs: { description: Docs, command: "make docs", priority: low }
+  lint: { description: Lint, command: "make lint" }
+  build: { description: Build, command: "make", priority: critical }
+  test: { description: Test, command: "make test", priority: high }
+  package: { description: Package, command: "make dist", depends_on: [build] }
+"#,
+    )
+    .unwrap();
+    let mut scheduler = Scheduler::new(graph);
+
+    assert_eq!(scheduler.schedule_next(), vec!["build", "test"]);
+    scheduler.mark_started("build").unwrap();
+    scheduler.mark_started("test").unwrap();
+    assert!(scheduler.schedule_next().is_empty());
+
+    scheduler.mark_done("build").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["lint"]);
+    scheduler.mark_started("lint").unwrap();
+    scheduler.mark_done("test").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["package"]);
+    scheduler.mark_started("package").unwrap();
+    scheduler.mark_done("lint").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["docs"]);
+}
+
+#[test]
+fn test_scheduler_holds_gates_for_approval() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  build: { description: Build, command: "make" }
+  approve-staging: { description: Approve staging deploy, type: gate, depends_on: [build] }
+  deploy: { description: Deploy, command: "make deploy", depends_on: [approve-staging] }
+  approve-prod: { description: Approve prod deploy, type: gate, depends_on: [deploy] }
+  prod: { description: Prod, command: "make prod", depends_on: [approve-prod] }
+"#,
+    )
+    .unwrap();
+    let mut scheduler = Scheduler::new(graph);
+
+    assert_eq!(scheduler.schedule_next(), vec!["build"]);
+    scheduler.mark_started("build").unwrap();
+    assert!(scheduler.approve("approve-staging").is_err());
+    scheduler.mark_done("build").unwrap();
+
+    // The gate is held instead of being returned to run
+    assert!(scheduler.schedule_next().is_empty());
+    assert_eq!(scheduler.take_awaiting_approval(), vec!["approve-staging"]);
+    assert_eq!(scheduler.awaiting_approval(), vec!["approve-staging"]);
+    assert!(scheduler.schedule_next().is_empty());
+    assert!(scheduler.take_awaiting_approval().is_empty());
     assert!(!scheduler.all_done());
+
+    scheduler.approve("approve-staging").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["deploy"]);
+    scheduler.mark_started("deploy").unwrap();
+    scheduler.mark_done("deploy").unwrap();
+
+    assert!(scheduler.schedule_next().is_empty());
+    scheduler.reject("approve-prod").unwrap();
+    assert_eq!(
+        scheduler.graph().get_task("prod").unwrap().status,
+        GraphTaskStatus::Skipped
+    );
+    assert!(scheduler.awaiting_approval().is_empty());
+    assert!(scheduler.all_done());
+}
+
+#[test]
+fn test_scheduler_apply_reloaded_graph() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  build: { description: Build, command: "make" }
+  serve: { description: Serve, command: "make serve", depends_on: [build] }
+  lint: { description: Lint, command: "make lint" }
+  docs: { description: Docs, command: "make docs" }
+"#,
+    )
+    .unwrap();
+    let mut scheduler = Scheduler::new(graph);
+    scheduler.mark_started("build").unwrap();
+    scheduler.mark_done("build").unwrap();
+    scheduler.mark_started("serve").unwrap();
+
+    let reloaded: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  build: { description: Build, command: "make -j8" }
+  serve: { description: Serve, command: "make serve-dev", depends_on: [build] }
+  docs: { description: Docs, command: "make docs" }
+  test: { description: Test, command: "make test", depends_on: [build] }
+"#,
+    )
+    .unwrap();
+    let diff = scheduler.apply_graph(reloaded);
+    assert_eq!(diff.added, vec!["test"]);
+    assert_eq!(diff.updated, vec!["build"]);
+    assert_eq!(diff.removed, vec!["lint"]);
+    assert_eq!(diff.kept_running, vec!["serve"]);
+
+    let graph = scheduler.graph();
+    assert_eq!(graph.get_task("build").unwrap().command.as_deref(), Some("make -j8"));
+    assert_eq!(graph.get_task("build").unwrap().status, GraphTaskStatus::Done);
+    assert_eq!(graph.get_task("serve").unwrap().command.as_deref(), Some("make serve"));
+    assert!(graph.get_task("lint").is_none());
+    assert_eq!(scheduler.schedule_next().len(), 2); // docs and test
+}
+
+#[test]
+fn test_scheduler_reloads_scheduling() {
+    use gidterm::core::{PolicyKind, SchedulingConfig};
+    use std::num::NonZeroUsize;
+
+    let tasks = r#"
+tasks:
+  a: { description: A, command: "true" }
+  b: { description: B, command: "true" }
+  c: { description: C, command: "true" }
+"#;
+    let graph: Graph = serde_yaml::from_str(tasks).unwrap();
+    let mut scheduler = Scheduler::new(graph);
+    // --policy on the command line, no limit yet
+    let overrides = SchedulingConfig { policy: Some(PolicyKind::FairShare), max_parallel: None };
+    scheduler.configure(&overrides, &HashMap::new());
+    assert_eq!(scheduler.policy_name(), "fair-share");
+
+    let reloaded: Graph = serde_yaml::from_str(&format!(
+        "scheduling: {{ policy: critical-path, max_parallel: 2 }}\n{}",
+        tasks
+    ))
+    .unwrap();
+    let diff = scheduler.apply_graph(reloaded);
+    assert!(diff.scheduling_changed);
+    assert_eq!(diff.to_string(), "0 added, 0 updated, 0 removed, scheduling changed");
+
+    // The graph's limit applies; the command-line policy still wins
+    assert_eq!(
+        scheduler.scheduling(),
+        SchedulingConfig { policy: Some(PolicyKind::FairShare), max_parallel: NonZeroUsize::new(2) }
+    );
+    assert_eq!(scheduler.policy_name(), "fair-share");
+    assert_eq!(scheduler.schedule_next().len(), 2);
+
+    // Reloading the same section changes nothing
+    let reloaded = scheduler.graph().clone();
+    assert!(scheduler.apply_graph(reloaded).is_empty());
+}
+
+#[test]
+fn test_scheduler_failure_conditions() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  lint: { description: Lint, command: "false", allow_failure: true }
+  build: { description: Build, command: "false" }
+  test: { description: Test, command: "true", depends_on: [lint, build] }
+  notify: { description: Notify, command: "true", depends_on: [test], on: failure }
+  teardown: { description: Teardown, command: "true", depends_on: [build], on: always }
+  celebrate: { description: Celebrate, command: "true", depends_on: [lint] }
+"#,
+    )
+    .unwrap();
+    let mut scheduler = Scheduler::new(graph);
+
+    let mut ready = scheduler.schedule_next();
+    ready.sort();
+    assert_eq!(ready, vec!["build", "lint"]);
+    scheduler.mark_started("lint").unwrap();
+    scheduler.mark_started("build").unwrap();
+
+    // A failure with allow_failure still counts as success for dependents
+    scheduler.mark_failed("lint").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["celebrate"]);
+    scheduler.mark_started("celebrate").unwrap();
+    scheduler.mark_done("celebrate").unwrap();
+
+    // build fails: test is skipped, notify (on failure) and teardown (always) run
+    scheduler.mark_failed("build").unwrap();
+    assert_eq!(scheduler.take_skipped(), vec!["test"]);
+    let mut ready = scheduler.schedule_next();
+    ready.sort();
+    assert_eq!(ready, vec!["notify", "teardown"]);
+}
+
+#[test]
+fn test_scheduler_skips_failure_handlers_on_success() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  build: { description: Build, command: "true" }
+  notify: { description: Notify, command: "true", depends_on: [build], on: failure }
+"#,
+    )
+    .unwrap();
+    let mut scheduler = Scheduler::new(graph);
+
+    scheduler.mark_started("build").unwrap();
+    scheduler.mark_done("build").unwrap();
+    assert!(scheduler.schedule_next().is_empty());
+    assert_eq!(scheduler.take_skipped(), vec!["notify"]);
+    assert!(scheduler.all_done());
+}
+
+#[test]
+fn test_scheduler_writes_status_back() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("graph.yml");
+    std::fs::copy("test-gid-integration.yml", &path).unwrap();
+
+    let graph = Graph::from_file(&path).unwrap();
+    let mut scheduler = Scheduler::new(graph);
+    scheduler.set_status_writer(gidterm::core::StatusWriter::single(&path));
+
+    scheduler.mark_started("hello").unwrap();
+    scheduler.mark_done("hello").unwrap();
+    scheduler.mark_started("world").unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains(r#"status: "done""#), "{}", written);
+    assert!(written.contains(r#"status: "in-progress""#), "{}", written);
+
+    // Everything except the two status lines is unchanged
+    let original = std::fs::read_to_string("test-gid-integration.yml").unwrap();
+    let changed = original
+        .lines()
+        .zip(written.lines())
+        .filter(|(a, b)| a != b)
+        .count();
+    assert_eq!(changed, 2);
+}
+
+#[test]
+fn test_interrupted_run_starts_again() {
+    use gidterm::core::StatusWriter;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("graph.yml");
+    std::fs::write(
+        &path,
+        r#"
+tasks:
+  build: { description: Build, command: make }
+  serve: { description: Serve, command: ./serve, kind: service, depends_on: [build] }
+  approve: { description: Approve, type: gate, depends_on: [build] }
+  deploy: { description: Deploy, command: ./deploy, depends_on: [approve] }
+"#,
+    )
+    .unwrap();
+
+    // Quit while serve is up and the gate is waiting
+    let mut scheduler = Scheduler::new(Graph::from_file(&path).unwrap());
+    scheduler.set_status_writer(StatusWriter::single(&path));
+    assert_eq!(scheduler.schedule_next(), vec!["build"]);
+    scheduler.mark_started("build").unwrap();
+    scheduler.mark_done("build").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["serve"]);
+    scheduler.mark_started("serve").unwrap();
+    scheduler.mark_ready("serve").unwrap();
+    assert_eq!(scheduler.take_awaiting_approval(), vec!["approve"]);
+    assert_eq!(scheduler.interrupt(), vec!["approve", "serve"]);
+    assert!(scheduler.get_running().is_empty());
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(!written.contains("in-progress"), "{}", written);
+    assert!(written.contains("serve: { status: pending,"), "{}", written);
+
+    // The next run picks up where the last one stopped
+    let mut scheduler = Scheduler::new(Graph::from_file(&path).unwrap());
+    assert_eq!(scheduler.graph().get_task("build").unwrap().status, GraphTaskStatus::Done);
+    assert_eq!(scheduler.schedule_next(), vec!["serve"]);
+    assert_eq!(scheduler.take_awaiting_approval(), vec!["approve"]);
+
+    // A run that died without shutting down leaves `in-progress` behind
+    std::fs::write(&path, written.replace("status: pending", "status: in-progress")).unwrap();
+    let mut scheduler = Scheduler::new(Graph::from_file(&path).unwrap());
+    assert_eq!(scheduler.graph().get_task("serve").unwrap().status, GraphTaskStatus::Pending);
+    assert_eq!(scheduler.schedule_next(), vec!["serve"]);
 }
 
 // === Session Tests ===
@@ -232,6 +604,7 @@ fn test_semantic_commands_from_graph() {
             estimated_hours: None,
             tags: None,
             semantic_commands: Some(sem_cmds),
+            ..Default::default()
         },
     );
 
@@ -251,3 +624,399 @@ fn test_semantic_commands_from_graph() {
         "optimizer.lr = 0.001"
     );
 }
+
+// === Executor Tests ===
+
+#[tokio::test]
+async fn test_executor_honors_cwd_env_and_shell() {
+    use gidterm::core::{Executor, TaskEvent};
+
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(dir.path().join(".env"), "FROM_FILE=file\nOVERRIDE=file\n").unwrap();
+
+    let task: gidterm::core::Task = serde_yaml::from_str(&format!(
+        r#"
+description: Print environment
+command: 'echo "$FROM_FILE $OVERRIDE $(basename "$PWD")"'
+cwd: {}
+env_file: .env
+env:
+  OVERRIDE: task
+shell: bash
+"#,
+        dir.path().display()
+    ))
+    .unwrap();
+
+    let (executor, mut rx) = Executor::new();
+    executor
+        .start_task("env", task.command.as_deref().unwrap(), task.spawn_options().unwrap())
+        .await
+        .unwrap();
+
+    let expected = format!(
+        "file task {}",
+        dir.path().file_name().unwrap().to_string_lossy()
+    );
+    let mut output = Vec::new();
+    while let Some(event) = tokio::time::timeout(std::time::Duration::from_secs(10), rx.recv())
+        .await
+        .unwrap()
+    {
+        match event {
+            TaskEvent::Started { pid, .. } => assert!(pid.is_some()),
+            TaskEvent::Output { line, .. } => output.push(line),
+            TaskEvent::Completed { .. } => break,
+            TaskEvent::Failed { error, .. } => panic!("task failed: {}", error),
+            _ => {}
+        }
+    }
+    assert!(output.contains(&expected), "output was {:?}", output);
+}
+
+#[tokio::test]
+async fn test_executor_times_out_and_escalates_to_kill() {
+    use gidterm::core::{Executor, TaskEvent};
+
+    // The shell and its child ignore SIGTERM, so only SIGKILL stops them
+    let task: gidterm::core::Task = serde_yaml::from_str(
+        r#"
+description: Hung task
+command: "trap '' TERM; sleep 30"
+timeout: 500ms
+kill_grace: 500ms
+"#,
+    )
+    .unwrap();
+
+    let (executor, mut rx) = Executor::new();
+    let started = std::time::Instant::now();
+    executor
+        .start_task("hung", task.command.as_deref().unwrap(), task.spawn_options().unwrap())
+        .await
+        .unwrap();
+
+    loop {
+        let event = tokio::time::timeout(std::time::Duration::from_secs(10), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TaskEvent::TimedOut { task_id, timeout } => {
+                assert_eq!(task_id, "hung");
+                assert_eq!(timeout, std::time::Duration::from_millis(500));
+                break;
+            }
+            TaskEvent::Completed { .. } => panic!("hung task completed"),
+            TaskEvent::Failed { error, .. } => panic!("task failed: {}", error),
+            _ => {}
+        }
+    }
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_executor_stop_task_stops_process_group() {
+    use gidterm::core::{Executor, SpawnOptions, TaskEvent};
+
+    // Running (not a zombie waiting to be reaped)
+    let alive = |pid: &str| {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .is_ok_and(|stat| stat.rsplit(')').next().is_some_and(|rest| !rest.trim_start().starts_with('Z')))
+    };
+
+    let options = SpawnOptions {
+        kill_grace: Some(std::time::Duration::from_millis(500)),
+        ..Default::default()
+    };
+    let (executor, mut rx) = Executor::new();
+    executor
+        .start_task("dev", "nohup sleep 30 >/dev/null 2>&1 & echo child=$!; wait", options)
+        .await
+        .unwrap();
+
+    let mut child = None;
+    loop {
+        let event = tokio::time::timeout(std::time::Duration::from_secs(10), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TaskEvent::Output { line, .. } if line.starts_with("child=") => {
+                child = Some(line.trim_start_matches("child=").trim().to_string());
+                executor.stop_task("dev").unwrap();
+            }
+            TaskEvent::Failed { .. } => break,
+            TaskEvent::Completed { .. } => panic!("stopped task completed"),
+            _ => {}
+        }
+    }
+
+    let child = child.expect("no child pid");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while alive(&child) && std::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(!alive(&child), "background process {} survived", child);
+}
+
+#[tokio::test]
+async fn test_executor_reports_exit_code_on_failure() {
+    use gidterm::core::{Executor, SpawnOptions, TaskEvent};
+
+    let (executor, mut rx) = Executor::new();
+    executor
+        .start_task("flaky", "echo flaky; exit 3", SpawnOptions::default())
+        .await
+        .unwrap();
+
+    loop {
+        let event = tokio::time::timeout(std::time::Duration::from_secs(10), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TaskEvent::Failed { exit_code, .. } => {
+                assert_eq!(exit_code, Some(3));
+                break;
+            }
+            TaskEvent::Completed { .. } => panic!("failing task completed"),
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_executor_output_capture() {
+    use gidterm::core::{Executor, OutputCapture, SpawnOptions, Task, TaskEvent, OUTPUT_FILE_ENV};
+
+    let task = Task {
+        description: "Build".to_string(),
+        outputs_capture: Some(HashMap::from([(
+            "tag".to_string(),
+            r"Successfully tagged (\S+)".to_string(),
+        )])),
+        ..Default::default()
+    };
+    let mut capture = OutputCapture::new(&task).unwrap();
+    let options = SpawnOptions {
+        env: HashMap::from([(
+            OUTPUT_FILE_ENV.to_string(),
+            capture.file().display().to_string(),
+        )]),
+        ..Default::default()
+    };
+
+    let (executor, mut rx) = Executor::new();
+    executor
+        .start_task(
+            "build",
+            "echo 'Successfully tagged app:42'; echo version=1.4.0 >> \"$GIDTERM_OUTPUT\"",
+            options,
+        )
+        .await
+        .unwrap();
+
+    loop {
+        let event = tokio::time::timeout(std::time::Duration::from_secs(10), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TaskEvent::Output { line, .. } => capture.scan_line(&line),
+            TaskEvent::Completed { .. } => break,
+            TaskEvent::Failed { error, .. } => panic!("task failed: {}", error),
+            _ => {}
+        }
+    }
+
+    let values = capture.finish();
+    assert_eq!(values["tag"], "app:42");
+    assert_eq!(values["version"], "1.4.0");
+}
+
+#[tokio::test]
+async fn test_executor_reports_ready_service() {
+    use gidterm::core::{Executor, ReadinessProbe, ReadyWhen, SpawnOptions, TaskEvent};
+
+    let ready_when = ReadyWhen {
+        output: Some("Listening on".to_string()),
+        interval: Some(std::time::Duration::from_millis(20).into()),
+        ..Default::default()
+    };
+    let options = SpawnOptions {
+        ready: Some(ReadinessProbe::new(&ready_when, None).unwrap()),
+        ..Default::default()
+    };
+
+    let (executor, mut rx) = Executor::new();
+    executor
+        .start_task("dev", "echo booting; sleep 0.2; echo 'Listening on :3000'; sleep 30", options)
+        .await
+        .unwrap();
+
+    loop {
+        let event = tokio::time::timeout(std::time::Duration::from_secs(10), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TaskEvent::Ready { task_id } => {
+                assert_eq!(task_id, "dev");
+                break;
+            }
+            TaskEvent::Completed { .. } | TaskEvent::Failed { .. } => panic!("service exited"),
+            _ => {}
+        }
+    }
+    assert!(executor.is_running("dev"));
+    executor.stop_all();
+}
+
+#[test]
+fn test_scheduler_ready_service_unblocks_dependents() {
+    let graph: Graph = serde_yaml::from_str(
+        r#"
+tasks:
+  dev: { description: Dev server, command: "npm run dev", kind: service, ready_when: { tcp: 3000 } }
+  e2e: { description: E2E, command: "npm run e2e", depends_on: [dev] }
+"#,
+    )
+    .unwrap();
+    assert!(graph.validate().issues.is_empty());
+    let mut scheduler = Scheduler::new(graph);
+
+    assert_eq!(scheduler.schedule_next(), vec!["dev"]);
+    scheduler.mark_started("dev").unwrap();
+    assert!(scheduler.schedule_next().is_empty());
+
+    scheduler.mark_ready("dev").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["e2e"]);
+    assert_eq!(scheduler.get_running(), vec!["dev"]);
+}
+
+#[test]
+fn test_graph_expands_matrix_on_load() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("graph.yml");
+    std::fs::write(
+        &path,
+        r#"
+tasks:
+  train:
+    description: Train
+    command: "python train.py --lr ${matrix.lr}"
+    matrix:
+      lr: [1e-3, 1e-4]
+  compare:
+    description: Compare runs
+    command: "python compare.py"
+    depends_on: [train]
+"#,
+    )
+    .unwrap();
+
+    let graph = Graph::from_file(&path).unwrap();
+    assert_eq!(
+        graph.matrix_groups()["train"],
+        vec!["train[lr=1e-3]", "train[lr=1e-4]"]
+    );
+
+    let mut scheduler = Scheduler::new(graph);
+    let mut ready = scheduler.schedule_next();
+    ready.sort();
+    assert_eq!(ready, vec!["train[lr=1e-3]", "train[lr=1e-4]"]);
+
+    // The group dependency waits for every variant
+    scheduler.mark_started("train[lr=1e-3]").unwrap();
+    scheduler.mark_done("train[lr=1e-3]").unwrap();
+    assert!(scheduler.schedule_next().iter().all(|id| id != "compare"));
+    scheduler.mark_started("train[lr=1e-4]").unwrap();
+    scheduler.mark_done("train[lr=1e-4]").unwrap();
+    assert_eq!(scheduler.schedule_next(), vec!["compare"]);
+}
+
+#[test]
+fn test_graph_from_toml_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(dir.path().join(".gid")).unwrap();
+    let path = dir.path().join(".gid/graph.toml");
+    std::fs::write(
+        &path,
+        r#"
+[tasks.build]
+description = "Build"
+command = "cargo build"
+
+[tasks.test]
+description = "Test on ${matrix.rust}"
+command = "cargo +${matrix.rust} test"
+depends_on = ["build"]
+matrix = { rust = ["stable", "nightly"] }
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(Graph::find_file(&dir.path().join(".gid"), "graph"), Some(path.clone()));
+    let graph = Graph::from_file(&path).unwrap();
+    assert_eq!(graph.tasks.len(), 3);
+    assert_eq!(
+        graph.get_task("test[rust=nightly]").unwrap().command.as_deref(),
+        Some("cargo +nightly test")
+    );
+}
+
+#[test]
+fn test_graph_vars_and_interpolation() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("shared.yml"),
+        "vars:\n  model: vgg\n  data: /data\ntasks:\n  fetch: { description: Fetch, command: \"fetch ${vars.data}\" }\n",
+    )
+    .unwrap();
+    let path = dir.path().join("graph.yml");
+    std::fs::write(
+        &path,
+        r#"
+include: [shared.yml]
+vars:
+  model: resnet50
+tasks:
+  train:
+    description: Train ${vars.model}
+    command: "python train.py --model ${vars.model} --port ${port} --out runs/${task.id}"
+    depends_on: [fetch]
+"#,
+    )
+    .unwrap();
+
+    let graph = Graph::from_file(&path).unwrap();
+    // The including file's vars win; included tasks see the merged set
+    let train = graph.get_task("train").unwrap();
+    assert_eq!(train.description, "Train resnet50");
+    assert_eq!(
+        graph.get_task("fetch").unwrap().command.as_deref(),
+        Some("fetch /data")
+    );
+
+    let ctx = gidterm::core::VarContext {
+        task_id: "train".to_string(),
+        port: Some(3005),
+        ..Default::default()
+    };
+    let resolved = gidterm::core::resolve_task(train, &ctx).unwrap();
+    assert_eq!(
+        resolved.command.as_deref(),
+        Some("python train.py --model resnet50 --port 3005 --out runs/train")
+    );
+
+    // Unknown vars fail validation instead of reaching the shell
+    std::fs::write(
+        &path,
+        "tasks:\n  train: { description: Train, command: \"train ${vars.model}\" }\n",
+    )
+    .unwrap();
+    let err = Graph::from_file(&path).unwrap_err().to_string();
+    assert!(err.contains("${vars.model}"), "{}", err);
+}